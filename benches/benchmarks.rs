@@ -1,8 +1,10 @@
 use criterion::black_box;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use rand::random;
-use rs_merkle_tree::stores::{MemoryStore, RocksDbStore, SledStore, SqliteStore};
-use rs_merkle_tree::{hasher::Keccak256Hasher, node::Node, tree::MerkleTree};
+use rs_merkle_tree::stores::{BlockingStore, MemoryStore, RocksDbStore, SledStore, SqliteStore};
+use rs_merkle_tree::{
+    async_tree::AsyncMerkleTree, hasher::Keccak256Hasher, node::Node, tree::MerkleTree,
+};
 
 // Constants for the benchmarks
 const BATCH_SIZE: u64 = 1000;
@@ -197,5 +199,64 @@ fn bench_get_proof(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_insertions, bench_get_proof);
+fn bench_async_get_proof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("async_get_proof");
+
+    group
+        .sample_size(SAMPLE_SIZE as usize)
+        .warm_up_time(std::time::Duration::from_millis(500));
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    // TODO: Improve the cleanups.
+    let _ = std::fs::remove_file("sqlite.db");
+    let _ = std::fs::remove_dir_all("sled.db");
+
+    let mut sqlite_tree: AsyncMerkleTree<Keccak256Hasher, BlockingStore<SqliteStore>, 32> =
+        AsyncMerkleTree::new(
+            Keccak256Hasher,
+            BlockingStore::new(SqliteStore::new("sqlite.db")),
+        );
+    let mut sled_tree: AsyncMerkleTree<Keccak256Hasher, BlockingStore<SledStore>, 32> =
+        AsyncMerkleTree::new(
+            Keccak256Hasher,
+            BlockingStore::new(SledStore::new("sled.db", false)),
+        );
+
+    runtime.block_on(async {
+        for _ in 0..NUM_BATCHES {
+            let leaves: Vec<Node> = (0..BATCH_SIZE)
+                .map(|_| black_box(Node::random()))
+                .collect::<Vec<Node>>();
+            sqlite_tree.add_leaves(&leaves).await.unwrap();
+            sled_tree.add_leaves(&leaves).await.unwrap();
+        }
+    });
+
+    group.bench_function(BenchmarkId::new("sqlite_store", "depth32_keccak256"), |b| {
+        b.to_async(&runtime).iter(|| async {
+            let i = random::<u64>() % (BATCH_SIZE * NUM_BATCHES);
+            sqlite_tree.proof(i).await.unwrap();
+        });
+    });
+    group.bench_function(BenchmarkId::new("sled_store", "depth32_keccak256"), |b| {
+        b.to_async(&runtime).iter(|| async {
+            let i = random::<u64>() % (BATCH_SIZE * NUM_BATCHES);
+            sled_tree.proof(i).await.unwrap();
+        });
+    });
+
+    // Cleanup
+    let _ = std::fs::remove_file("sqlite.db");
+    let _ = std::fs::remove_dir_all("sled.db");
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_insertions,
+    bench_get_proof,
+    bench_async_get_proof
+);
 criterion_main!(benches);
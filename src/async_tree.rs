@@ -0,0 +1,96 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Merkle tree implementation on top of an async store.
+
+use crate::hasher::Hasher;
+use crate::stores::AsyncStore;
+use crate::tree::{append_siblings, hash_append, path_from_fetched, path_keys, MerkleProof, Zeros};
+use crate::{MerkleError, Node};
+
+/// Same as `MerkleTree` but reading and writing nodes through an `AsyncStore`, so it can be
+/// used from async code without blocking the executor. Nodes are stored in the same layout,
+/// so a store written by one can be read by the other. Wrap a blocking store in a
+/// `BlockingStore` to use it here.
+pub struct AsyncMerkleTree<H, S, const DEPTH: usize>
+where
+    H: Hasher,
+    S: AsyncStore,
+{
+    hasher: H,
+    store: S,
+    zeros: Zeros<DEPTH>,
+}
+
+impl<H, S, const DEPTH: usize> AsyncMerkleTree<H, S, DEPTH>
+where
+    H: Hasher,
+    S: AsyncStore,
+{
+    pub fn new(hasher: H, store: S) -> Self {
+        const { assert!(DEPTH >= 1 && DEPTH <= 64, "DEPTH must be between 1 and 64") };
+
+        let zeros = Zeros::new(&hasher);
+        Self {
+            hasher,
+            store,
+            zeros,
+        }
+    }
+
+    pub async fn add_leaves(&mut self, leaves: &[Node]) -> Result<(), MerkleError> {
+        // Early return
+        if leaves.is_empty() {
+            return Ok(());
+        }
+
+        // All the siblings not computed in this batch are read at once.
+        let num_leaves = self.store.get_num_leaves().await;
+        let (levels, indices) = append_siblings(DEPTH, num_leaves);
+        let siblings = self.store.get(levels, indices).await?;
+
+        let batch = hash_append(
+            &self.hasher,
+            &self.zeros,
+            DEPTH,
+            num_leaves,
+            leaves,
+            &siblings,
+        )?;
+        self.store.put(batch).await
+    }
+
+    pub async fn proof(&self, leaf_idx: u64) -> Result<MerkleProof<DEPTH>, MerkleError> {
+        let (levels, indices) = path_keys(DEPTH, leaf_idx)?;
+        let fetched = self.store.get(levels, indices).await?;
+        let (siblings, leaf, root) = path_from_fetched(&self.zeros, DEPTH, &fetched);
+
+        let mut proof = [Node::ZERO; DEPTH];
+        proof.copy_from_slice(&siblings);
+
+        Ok(MerkleProof {
+            proof,
+            leaf,
+            index: leaf_idx,
+            root,
+        })
+    }
+
+    pub async fn root(&self) -> Result<Node, MerkleError> {
+        Ok(self
+            .store
+            .get(vec![DEPTH as u32], vec![0])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| MerkleError::StoreError("root fetch returned empty vector".into()))?
+            .unwrap_or(self.zeros[DEPTH]))
+    }
+
+    pub async fn num_leaves(&self) -> u64 {
+        self.store.get_num_leaves().await
+    }
+
+    pub fn verify_proof(&self, proof: &MerkleProof<DEPTH>) -> bool {
+        proof.verify(&self.hasher)
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Merkle tree implementation with the depth chosen at runtime.
+
+use crate::hasher::Hasher;
+use crate::tree::{append_batch, capacity, compute_root, read_path};
+use crate::{MerkleError, Node, Store};
+
+/// Proof of a leaf in a `DynMerkleTree`, with one sibling per level.
+pub struct DynMerkleProof {
+    pub proof: Vec<Node>,
+    pub leaf: Node,
+    pub index: u64,
+    pub root: Node,
+}
+
+impl DynMerkleProof {
+    /// Verifies the proof without needing the tree.
+    pub fn verify<H: Hasher>(&self, hasher: &H) -> bool {
+        compute_root(hasher, &self.leaf, self.index, &self.proof) == self.root
+    }
+}
+
+/// Same as `MerkleTree` but with the depth given to the constructor instead of as a const
+/// generic, so trees of different depths can have the same type, e.g. when the depth
+/// comes from a configuration file. Nodes are stored in the same layout, so both types can
+/// be used with the same store.
+pub struct DynMerkleTree<H, S>
+where
+    H: Hasher,
+    S: Store,
+{
+    hasher: H,
+    store: S,
+    depth: usize,
+    // Root of an empty subtree at each level, from the empty leaf up to the empty root.
+    zeros: Vec<Node>,
+}
+
+impl<H, S> DynMerkleTree<H, S>
+where
+    H: Hasher,
+    S: Store,
+{
+    /// Creates a tree of `depth` levels. Panics if the depth is not between 1 and 64 or the
+    /// store can't be used. See `try_new`.
+    pub fn new(hasher: H, store: S, depth: usize) -> Self {
+        Self::try_new(hasher, store, depth).expect("failed to create merkle tree")
+    }
+
+    /// Creates a tree of `depth` levels on top of `store`. Errors if the depth is not
+    /// between 1 and 64 or the store holds more leaves than fit in the tree.
+    pub fn try_new(hasher: H, store: S, depth: usize) -> Result<Self, MerkleError> {
+        if !(1..=64).contains(&depth) {
+            return Err(MerkleError::InvalidDepth { depth });
+        }
+
+        let num_leaves = store.get_num_leaves();
+        if num_leaves > capacity(depth) {
+            return Err(MerkleError::CapacityExceeded {
+                num_leaves,
+                capacity: capacity(depth),
+            });
+        }
+
+        let mut zeros = vec![Node::ZERO; depth + 1];
+        for i in 1..=depth {
+            zeros[i] = hasher.hash(&zeros[i - 1], &zeros[i - 1]);
+        }
+
+        let tree = Self {
+            hasher,
+            store,
+            depth,
+            zeros,
+        };
+
+        // Surface store errors now rather than on first use.
+        tree.root()?;
+        Ok(tree)
+    }
+
+    pub fn add_leaves(&mut self, leaves: &[Node]) -> Result<(), MerkleError> {
+        // Early return
+        if leaves.is_empty() {
+            return Ok(());
+        }
+
+        let batch = append_batch(
+            &self.hasher,
+            &self.store,
+            &self.zeros[..],
+            self.depth,
+            leaves,
+        )?;
+        self.store.put(&batch)
+    }
+
+    pub fn proof(&self, leaf_idx: u64) -> Result<DynMerkleProof, MerkleError> {
+        let (proof, leaf, root) = read_path(&self.store, &self.zeros[..], self.depth, leaf_idx)?;
+        Ok(DynMerkleProof {
+            proof,
+            leaf,
+            index: leaf_idx,
+            root,
+        })
+    }
+
+    pub fn verify_proof(&self, proof: &DynMerkleProof) -> bool {
+        proof.verify(&self.hasher)
+    }
+
+    pub fn root(&self) -> Result<Node, MerkleError> {
+        Ok(self.store.get(&[self.depth as u32], &[0])?[0].unwrap_or(self.zeros[self.depth]))
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn num_leaves(&self) -> u64 {
+        self.store.get_num_leaves()
+    }
+}
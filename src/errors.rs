@@ -1,14 +1,58 @@
+use crate::Node;
+
 #[derive(Debug, thiserror::Error)]
 pub enum MerkleError {
     #[error("Error storing data: {0}")]
     StoreError(String),
 
+    #[error("Error opening store: {0}")]
+    StoreOpenError(String),
+
+    #[error("Error initializing store: {0}")]
+    StoreInitError(String),
+
+    #[error("Invalid metadata: key: {key}, length: {len}")]
+    InvalidMetadata { key: String, len: usize },
+
+    #[error("Store exceeds the tree capacity: num_leaves: {num_leaves}, capacity: {capacity}")]
+    CapacityExceeded { num_leaves: u64, capacity: u64 },
+
+    #[error("Invalid depth: {depth}, must be between 1 and 64")]
+    InvalidDepth { depth: usize },
+
+    #[error("Arity not supported by the hasher: {arity}, max: {max}")]
+    UnsupportedArity { arity: usize, max: usize },
+
     #[error("Leaf index out of bounds: {index}, num_leaves: {num_leaves}")]
     LeafIndexOutOfBounds { index: u64, num_leaves: u64 },
 
+    #[error("Empty range: start: {start}, end: {end}")]
+    EmptyRange { start: u64, end: u64 },
+
     #[error("Tree is full: depth: {depth}, capacity: {capacity}")]
     TreeFull { depth: u32, capacity: u64 },
 
     #[error("Levels and indices must have the same length")]
     LengthMismatch { levels: usize, indices: usize },
+
+    #[error("Number of leaves mismatch: expected: {expected}, actual: {actual}")]
+    LeafCountMismatch { expected: u64, actual: u64 },
+
+    #[error("Root mismatch: expected: {expected}, actual: {actual}")]
+    RootMismatch { expected: Node, actual: Node },
+
+    #[error("Key already exists: {key}")]
+    KeyExists { key: Node },
+
+    #[error("Key not found: {key}")]
+    KeyNotFound { key: Node },
+
+    #[error("Value can't be zero: key: {key}")]
+    ZeroValue { key: Node },
+
+    #[error("Frontier doesn't match its number of leaves: {num_leaves}")]
+    InvalidFrontier { num_leaves: u64 },
+
+    #[error("Tree lock poisoned by a panicking writer")]
+    LockPoisoned,
 }
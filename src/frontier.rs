@@ -0,0 +1,206 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Frontier-only Merkle tree implementation.
+
+use crate::hasher::Hasher;
+use crate::tree::{capacity, MerkleTree, Zeros};
+use crate::{MerkleError, Node, Store};
+
+/// State of a `FrontierTree`, without its hasher. It can be persisted and the tree restored
+/// from it with `FrontierTree::from_parts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontierParts<const DEPTH: usize> {
+    /// Right-most complete subtree at each level. Only the levels where the bit of
+    /// `num_leaves` is set are meaningful.
+    pub branch: [Node; DEPTH],
+    /// Root of the tree once it's full.
+    pub full_root: Option<Node>,
+    pub num_leaves: u64,
+}
+
+/// Merkle tree that only keeps the right-most complete subtree at each level, plus the
+/// number of leaves, like the Ethereum deposit contract. It takes `O(DEPTH)` space and
+/// produces the same roots as a `MerkleTree` with the same hasher and depth, but it can't
+/// generate proofs.
+pub struct FrontierTree<H, const DEPTH: usize>
+where
+    H: Hasher,
+{
+    hasher: H,
+    zeros: Zeros<DEPTH>,
+    // Right-most complete subtree at each level. Only the levels where the bit of the
+    // number of leaves is set are meaningful.
+    branch: [Node; DEPTH],
+    // Root of the tree once it's full, since it doesn't fit in the branch.
+    full_root: Option<Node>,
+    num_leaves: u64,
+}
+
+impl<H, const DEPTH: usize> FrontierTree<H, DEPTH>
+where
+    H: Hasher,
+{
+    pub fn new(hasher: H) -> Self {
+        const { assert!(DEPTH >= 1 && DEPTH <= 64, "DEPTH must be between 1 and 64") };
+
+        let zeros = Zeros::new(&hasher);
+        Self {
+            hasher,
+            zeros,
+            branch: [Node::ZERO; DEPTH],
+            full_root: None,
+            num_leaves: 0,
+        }
+    }
+
+    /// Restores a tree from the state returned by `into_parts`. Errors if there are more
+    /// leaves than fit in the tree, or if `full_root` is set but the tree isn't full or
+    /// the other way around. The branch itself can't be checked against the leaves.
+    pub fn from_parts(hasher: H, parts: FrontierParts<DEPTH>) -> Result<Self, MerkleError> {
+        let capacity = capacity(DEPTH);
+        if parts.num_leaves > capacity {
+            return Err(MerkleError::CapacityExceeded {
+                num_leaves: parts.num_leaves,
+                capacity,
+            });
+        }
+
+        // A depth of 64 doesn't fit 2^64 leaves, so it's never full.
+        let is_full = DEPTH < u64::BITS as usize && parts.num_leaves == capacity;
+        if parts.full_root.is_some() != is_full {
+            return Err(MerkleError::InvalidFrontier {
+                num_leaves: parts.num_leaves,
+            });
+        }
+
+        let mut tree = Self::new(hasher);
+        tree.branch = parts.branch;
+        tree.full_root = parts.full_root;
+        tree.num_leaves = parts.num_leaves;
+        Ok(tree)
+    }
+
+    /// Returns the state of the tree, to restore it later with `from_parts`.
+    pub fn into_parts(self) -> FrontierParts<DEPTH> {
+        FrontierParts {
+            branch: self.branch,
+            full_root: self.full_root,
+            num_leaves: self.num_leaves,
+        }
+    }
+
+    pub fn add_leaves(&mut self, leaves: &[Node]) -> Result<(), MerkleError> {
+        self.add_leaves_with(leaves, |_, _, _| {})
+    }
+
+    /// Adds the `leaves` calling `on_node` with the level, index and hash of every node
+    /// that gets completed, starting at the leaves themselves.
+    pub(crate) fn add_leaves_with(
+        &mut self,
+        leaves: &[Node],
+        mut on_node: impl FnMut(usize, u64, &Node),
+    ) -> Result<(), MerkleError> {
+        // Error if leaves do not fit in the tree
+        let capacity = capacity(DEPTH);
+        if self
+            .num_leaves
+            .checked_add(leaves.len() as u64)
+            .is_none_or(|num_leaves| num_leaves > capacity)
+        {
+            return Err(MerkleError::TreeFull {
+                depth: DEPTH as u32,
+                capacity,
+            });
+        }
+
+        for leaf in leaves {
+            self.add_leaf(*leaf, &mut on_node);
+        }
+
+        Ok(())
+    }
+
+    fn add_leaf(&mut self, leaf: Node, on_node: &mut impl FnMut(usize, u64, &Node)) {
+        let index = self.num_leaves;
+        self.num_leaves += 1;
+
+        // Hash the complete subtrees on the left until reaching an incomplete one.
+        let mut h = leaf;
+        let mut size = self.num_leaves;
+        for level in 0..DEPTH {
+            on_node(level, index >> level, &h);
+            if size & 1 == 1 {
+                self.branch[level] = h;
+                return;
+            }
+            h = self.hasher.hash(&self.branch[level], &h);
+            size >>= 1;
+        }
+
+        self.full_root = Some(h);
+    }
+
+    pub fn root(&self) -> Node {
+        match self.full_root {
+            Some(root) => root,
+            None => self.edge_node(DEPTH),
+        }
+    }
+
+    /// Returns the node at `level` covering the next leaf to be added, with the leaves
+    /// that are not added yet as empty.
+    pub(crate) fn edge_node(&self, level: usize) -> Node {
+        let mut h = self.zeros[0];
+        let mut size = self.num_leaves;
+        for l in 0..level {
+            h = if size & 1 == 1 {
+                self.hasher.hash(&self.branch[l], &h)
+            } else {
+                self.hasher.hash(&h, &self.zeros[l])
+            };
+            size >>= 1;
+        }
+        h
+    }
+
+    /// Returns the last complete node at `level` with an even index.
+    pub(crate) fn branch(&self, level: usize) -> Node {
+        self.branch[level]
+    }
+
+    pub(crate) fn zeros(&self) -> &Zeros<DEPTH> {
+        &self.zeros
+    }
+
+    pub fn num_leaves(&self) -> u64 {
+        self.num_leaves
+    }
+
+    /// Converts the frontier into a full `MerkleTree` by replaying all its `leaves` into
+    /// an empty `store`. Errors if the leaves don't produce the same tree.
+    pub fn into_merkle_tree<S: Store>(
+        self,
+        store: S,
+        leaves: &[Node],
+    ) -> Result<MerkleTree<H, S, DEPTH>, MerkleError> {
+        let expected = self.root();
+        let num_leaves = self.num_leaves;
+
+        let mut tree = MerkleTree::try_new(self.hasher, store)?;
+        tree.add_leaves(leaves)?;
+
+        if tree.num_leaves() != num_leaves {
+            return Err(MerkleError::LeafCountMismatch {
+                expected: num_leaves,
+                actual: tree.num_leaves(),
+            });
+        }
+
+        let actual = tree.root()?;
+        if actual != expected {
+            return Err(MerkleError::RootMismatch { expected, actual });
+        }
+
+        Ok(tree)
+    }
+}
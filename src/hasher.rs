@@ -5,8 +5,26 @@ use ark_bn254::Fr;
 use light_poseidon::{Poseidon, PoseidonBytesHasher};
 use tiny_keccak::{Hasher as KeccakHasher, Keccak};
 
-pub trait Hasher {
+/// Hashers are `Sync` so that nodes can be hashed from several threads, as done with the
+/// `parallel` feature.
+pub trait Hasher: Sync {
     fn hash(&self, left: &Node, right: &Node) -> Node;
+
+    /// Hashes the children of a node in an N-ary tree. By default, the children are
+    /// folded from the left with `hash`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nodes` is empty or holds more than `max_inputs` nodes.
+    fn hash_many(&self, nodes: &[Node]) -> Node {
+        let (first, rest) = nodes.split_first().expect("at least one node");
+        rest.iter().fold(*first, |acc, node| self.hash(&acc, node))
+    }
+
+    /// Returns the largest number of nodes `hash_many` accepts. Unlimited by default.
+    fn max_inputs(&self) -> usize {
+        usize::MAX
+    }
 }
 
 // Implements the keccak256 hash function.
@@ -21,6 +39,16 @@ impl Hasher for Keccak256Hasher {
         keccak.finalize(&mut buf);
         Node::from(buf)
     }
+
+    fn hash_many(&self, nodes: &[Node]) -> Node {
+        let mut keccak = Keccak::v256();
+        for node in nodes {
+            keccak.update(node.as_ref());
+        }
+        let mut buf = [0u8; 32];
+        keccak.finalize(&mut buf);
+        Node::from(buf)
+    }
 }
 
 // Implements the circom-compatible Poseidon hash function (T=3)
@@ -37,6 +65,21 @@ impl Hasher for PoseidonHasher {
 
         Node::from(res)
     }
+
+    // circom-compatible Poseidon with one input per node (T=N+1), from 1 up to 12 inputs
+    fn hash_many(&self, nodes: &[Node]) -> Node {
+        let mut poseidon = Poseidon::<Fr>::new_circom(nodes.len())
+            .unwrap_or_else(|_| panic!("poseidon takes 1 to 12 inputs, got {}", nodes.len()));
+
+        let inputs: Vec<&[u8]> = nodes.iter().map(|node| node.as_ref()).collect();
+        let res = poseidon.hash_bytes_be(&inputs).unwrap();
+
+        Node::from(res)
+    }
+
+    fn max_inputs(&self) -> usize {
+        12
+    }
 }
 
 #[cfg(test)]
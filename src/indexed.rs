@@ -0,0 +1,329 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Indexed Merkle tree implementation.
+
+use crate::hasher::Hasher;
+use crate::tree::{capacity, MerkleProof, MerkleTree};
+use crate::{MerkleError, Node, Store};
+use std::collections::BTreeMap;
+
+const LEAF_LEN: usize = Node::LEN + 8 + Node::LEN;
+
+/// Number of leaf preimages read from the store at once when opening the tree.
+const LOAD_PAGE_SIZE: u64 = 1024;
+
+fn leaf_key(index: u64) -> String {
+    format!("INDEXED_LEAF_{}", index)
+}
+
+// Returns the nodes on the path of `leaf` at `index`, from the leaf up to the root.
+fn path<H: Hasher>(hasher: &H, leaf: Node, index: u64, siblings: &[Node]) -> Vec<Node> {
+    let mut path = Vec::with_capacity(siblings.len() + 1);
+    path.push(leaf);
+    for (level, sibling) in siblings.iter().enumerate() {
+        let h = path[level];
+        path.push(if (index >> level) & 1 == 0 {
+            hasher.hash(&h, sibling)
+        } else {
+            hasher.hash(sibling, &h)
+        });
+    }
+    path
+}
+
+/// Preimage of a leaf in an `IndexedMerkleTree`. Leaves form a linked list sorted by
+/// value, where the last one points to index and value zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexedLeaf {
+    pub value: Node,
+    pub next_index: u64,
+    pub next_value: Node,
+}
+
+impl IndexedLeaf {
+    /// The leaf at index zero, with value zero, which is always in the tree.
+    const SENTINEL: IndexedLeaf = IndexedLeaf {
+        value: Node::ZERO,
+        next_index: 0,
+        next_value: Node::ZERO,
+    };
+
+    /// Returns the leaf hash `H(H(value, next_index), next_value)`, where the index is
+    /// encoded as a big-endian node.
+    pub fn hash<H: Hasher>(&self, hasher: &H) -> Node {
+        let mut next_index = [0u8; Node::LEN];
+        next_index[Node::LEN - 8..].copy_from_slice(&self.next_index.to_be_bytes());
+        hasher.hash(
+            &hasher.hash(&self.value, &Node::from(next_index)),
+            &self.next_value,
+        )
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(LEAF_LEN);
+        bytes.extend_from_slice(self.value.as_ref());
+        bytes.extend_from_slice(&self.next_index.to_be_bytes());
+        bytes.extend_from_slice(self.next_value.as_ref());
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, MerkleError> {
+        if bytes.len() != LEAF_LEN {
+            return Err(MerkleError::StoreError(
+                "invalid indexed leaf length".into(),
+            ));
+        }
+        let (value, rest) = bytes.split_at(Node::LEN);
+        let (next_index, next_value) = rest.split_at(8);
+        Ok(IndexedLeaf {
+            value: Node::from(<[u8; Node::LEN]>::try_from(value).expect("checked length")),
+            next_index: u64::from_be_bytes(next_index.try_into().expect("checked length")),
+            next_value: Node::from(
+                <[u8; Node::LEN]>::try_from(next_value).expect("checked length"),
+            ),
+        })
+    }
+}
+
+/// Witness of an insertion, which updates the low leaf to point to the new value and
+/// appends the new leaf, as needed to prove it in a circuit.
+pub struct InsertionWitness<const DEPTH: usize> {
+    /// Low leaf before the insertion.
+    pub low_leaf: IndexedLeaf,
+    /// Proof of the low leaf against the root before the insertion.
+    pub low_leaf_proof: MerkleProof<DEPTH>,
+    pub new_leaf: IndexedLeaf,
+    /// Proof of the empty slot of the new leaf against the root after updating the low
+    /// leaf.
+    pub new_leaf_proof: MerkleProof<DEPTH>,
+}
+
+/// Proof that `value` is not in an `IndexedMerkleTree`, given by the low leaf: the leaf
+/// with the largest value below it, which points to a larger value or to the end of the
+/// list.
+pub struct NonMembershipProof<const DEPTH: usize> {
+    pub value: Node,
+    pub low_leaf: IndexedLeaf,
+    pub proof: MerkleProof<DEPTH>,
+}
+
+impl<const DEPTH: usize> NonMembershipProof<DEPTH> {
+    /// Verifies the proof without needing the tree.
+    pub fn verify<H: Hasher>(&self, hasher: &H) -> bool {
+        self.low_leaf.value < self.value
+            && (self.value < self.low_leaf.next_value || self.low_leaf.next_value == Node::ZERO)
+            && self.proof.leaf == self.low_leaf.hash(hasher)
+            && self.proof.verify(hasher)
+    }
+}
+
+/// Aztec-style indexed Merkle tree, built on top of a `MerkleTree`. Each leaf is the hash
+/// of a value and the index and value of the next larger one, so that the absence of a
+/// value is proven with the leaf that would precede it. Values are nodes compared as
+/// big-endian numbers, and zero is always in the tree.
+///
+/// Leaf preimages are stored as metadata, and the values are also kept in memory sorted,
+/// to find the low leaf of a new value without reading the store. Since stores can't
+/// search metadata by value, this index is rebuilt from every preimage when the tree is
+/// opened, so opening it and its memory use grow linearly with the number of values,
+/// at least 40 bytes each. It suits sets of up to a few tens of millions of values.
+pub struct IndexedMerkleTree<H, S, const DEPTH: usize>
+where
+    H: Hasher,
+    S: Store,
+{
+    tree: MerkleTree<H, S, DEPTH>,
+    values: BTreeMap<Node, u64>,
+}
+
+impl<H, S, const DEPTH: usize> IndexedMerkleTree<H, S, DEPTH>
+where
+    H: Hasher,
+    S: Store,
+{
+    /// Creates the tree, panicking if the store can't be read. See `try_new`.
+    pub fn new(hasher: H, store: S) -> Self {
+        Self::try_new(hasher, store).expect("failed to create indexed merkle tree")
+    }
+
+    /// Creates the tree, loading the values already in the store, or adding the zero
+    /// leaf if it's empty.
+    ///
+    /// This reads the preimages of all the leaves to build the in-memory index of values,
+    /// so it takes `O(n)` time and memory in the number of leaves. See
+    /// `IndexedMerkleTree`.
+    pub fn try_new(hasher: H, store: S) -> Result<Self, MerkleError> {
+        let mut tree = MerkleTree::try_new(hasher, store)?;
+
+        if tree.num_leaves() == 0 {
+            let sentinel = IndexedLeaf::SENTINEL;
+            tree.add_leaves_with_metadata(
+                &[sentinel.hash(tree.hasher())],
+                vec![(leaf_key(0), sentinel.encode())],
+            )?;
+        }
+
+        // The preimages are read in pages, so only the values are held at once.
+        let mut values = BTreeMap::new();
+        let num_leaves = tree.num_leaves();
+        for start in (0..num_leaves).step_by(LOAD_PAGE_SIZE as usize) {
+            let end = num_leaves.min(start + LOAD_PAGE_SIZE);
+            let keys: Vec<String> = (start..end).map(leaf_key).collect();
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            for (index, bytes) in (start..).zip(tree.store().get_metadata_many(&keys)?) {
+                let bytes = bytes.ok_or_else(|| {
+                    MerkleError::StoreError(format!("missing indexed leaf {}", index))
+                })?;
+                values.insert(IndexedLeaf::decode(&bytes)?.value, index);
+            }
+        }
+
+        Ok(Self { tree, values })
+    }
+
+    /// Returns the preimage of the leaf at `index`.
+    pub fn leaf(&self, index: u64) -> Result<IndexedLeaf, MerkleError> {
+        let num_leaves = self.tree.num_leaves();
+        if index >= num_leaves {
+            return Err(MerkleError::LeafIndexOutOfBounds { index, num_leaves });
+        }
+
+        let bytes = self
+            .tree
+            .store()
+            .get_metadata(&leaf_key(index))?
+            .ok_or_else(|| MerkleError::StoreError(format!("missing indexed leaf {}", index)))?;
+        IndexedLeaf::decode(&bytes)
+    }
+
+    /// Returns the index of `value`, or None if it's not in the tree.
+    pub fn index_of(&self, value: &Node) -> Option<u64> {
+        self.values.get(value).copied()
+    }
+
+    /// Returns the index and preimage of the leaf with the largest value below `value`.
+    pub fn low_leaf(&self, value: &Node) -> Result<(u64, IndexedLeaf), MerkleError> {
+        let Some((_, index)) = self.values.range(..value).next_back() else {
+            return Err(MerkleError::KeyExists { key: *value });
+        };
+        Ok((*index, self.leaf(*index)?))
+    }
+
+    /// Inserts `value` by updating its low leaf to point to it and appending its leaf,
+    /// which points to the value the low leaf pointed to. Errors if the value is already
+    /// in the tree.
+    ///
+    /// Both leaves are written in a single batch. The proof of the empty slot of the new
+    /// leaf after updating the low leaf is computed from the paths read before.
+    pub fn insert(&mut self, value: Node) -> Result<InsertionWitness<DEPTH>, MerkleError> {
+        if self.values.contains_key(&value) {
+            return Err(MerkleError::KeyExists { key: value });
+        }
+
+        let new_index = self.tree.num_leaves();
+        if new_index >= capacity(DEPTH) {
+            return Err(MerkleError::TreeFull {
+                depth: DEPTH as u32,
+                capacity: capacity(DEPTH),
+            });
+        }
+
+        let (low_index, low_leaf) = self.low_leaf(&value)?;
+        let low_leaf_proof = self.tree.proof(low_index)?;
+        let mut new_leaf_proof = self.tree.proof(new_index)?;
+
+        let new_leaf = IndexedLeaf {
+            value,
+            next_index: low_leaf.next_index,
+            next_value: low_leaf.next_value,
+        };
+        let updated_low_leaf = IndexedLeaf {
+            next_index: new_index,
+            next_value: value,
+            ..low_leaf
+        };
+
+        // Path of the low leaf after the update. Where it's the sibling of the path of the
+        // new leaf, it replaces the sibling read before.
+        let hasher = self.tree.hasher();
+        let low_path = path(
+            hasher,
+            updated_low_leaf.hash(hasher),
+            low_index,
+            &low_leaf_proof.proof,
+        );
+        for (level, sibling) in new_leaf_proof.proof.iter_mut().enumerate() {
+            if (new_index >> level) ^ 1 == low_index >> level {
+                *sibling = low_path[level];
+            }
+        }
+        new_leaf_proof.root = low_path[DEPTH];
+
+        // Path of the new leaf, which overrides the one of the low leaf from the level
+        // where they meet.
+        let new_path = path(
+            hasher,
+            new_leaf.hash(hasher),
+            new_index,
+            &new_leaf_proof.proof,
+        );
+        let mut batch: Vec<(u32, u64, Node)> = Vec::with_capacity(2 * (DEPTH + 1));
+        for (level, node) in low_path.iter().enumerate() {
+            if low_index >> level == new_index >> level {
+                break;
+            }
+            batch.push((level as u32, low_index >> level, *node));
+        }
+        for (level, node) in new_path.iter().enumerate() {
+            batch.push((level as u32, new_index >> level, *node));
+        }
+
+        // The inner tree keeps no root history nor leaf index, so only the preimages are
+        // written along with the nodes.
+        self.tree.put_batch(
+            &batch,
+            &[
+                (leaf_key(low_index), updated_low_leaf.encode()),
+                (leaf_key(new_index), new_leaf.encode()),
+            ],
+        )?;
+        self.values.insert(value, new_index);
+
+        Ok(InsertionWitness {
+            low_leaf,
+            low_leaf_proof,
+            new_leaf,
+            new_leaf_proof,
+        })
+    }
+
+    /// Returns the proof that `value` is not in the tree. Errors if it is.
+    pub fn non_membership_proof(
+        &self,
+        value: &Node,
+    ) -> Result<NonMembershipProof<DEPTH>, MerkleError> {
+        if self.values.contains_key(value) {
+            return Err(MerkleError::KeyExists { key: *value });
+        }
+
+        let (low_index, low_leaf) = self.low_leaf(value)?;
+        Ok(NonMembershipProof {
+            value: *value,
+            low_leaf,
+            proof: self.tree.proof(low_index)?,
+        })
+    }
+
+    pub fn root(&self) -> Result<Node, MerkleError> {
+        self.tree.root()
+    }
+
+    pub fn num_leaves(&self) -> u64 {
+        self.tree.num_leaves()
+    }
+
+    /// Returns the underlying tree, to get proofs of the leaves.
+    pub fn tree(&self) -> &MerkleTree<H, S, DEPTH> {
+        &self.tree
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Lean incremental Merkle tree implementation, compatible with zk-kit's LeanIMT.
+
+use crate::hasher::Hasher;
+use crate::{MerkleError, Node, Store};
+
+/// Proof of a leaf in a `LeanMerkleTree`. Levels where the node has no sibling are
+/// skipped, so `index` only has one bit per sibling, starting at the leaves.
+pub struct LeanMerkleProof {
+    pub siblings: Vec<Node>,
+    pub leaf: Node,
+    pub index: u64,
+    pub root: Node,
+}
+
+impl LeanMerkleProof {
+    /// Verifies the proof without needing the tree.
+    pub fn verify<H: Hasher>(&self, hasher: &H) -> bool {
+        if self.siblings.len() < u64::BITS as usize && self.index >> self.siblings.len() != 0 {
+            return false;
+        }
+
+        let mut h = self.leaf;
+        for (i, sib_hash) in self.siblings.iter().enumerate() {
+            h = if (self.index >> i) & 1 == 1 {
+                hasher.hash(sib_hash, &h)
+            } else {
+                hasher.hash(&h, sib_hash)
+            };
+        }
+        h == self.root
+    }
+}
+
+/// Merkle tree whose depth grows with the number of leaves, like zk-kit's LeanIMT used by
+/// Semaphore v4. Instead of hashing with empty subtrees, a node without a right sibling is
+/// carried up to the next level as is. The root is the single node at level `depth()`.
+pub struct LeanMerkleTree<H, S>
+where
+    H: Hasher,
+    S: Store,
+{
+    hasher: H,
+    store: S,
+}
+
+// Returns the depth of a tree with `num_leaves` leaves.
+fn depth_for(num_leaves: u64) -> u32 {
+    if num_leaves <= 1 {
+        0
+    } else {
+        u64::BITS - (num_leaves - 1).leading_zeros()
+    }
+}
+
+// Returns the number of nodes at `level` of a tree with `num_leaves` leaves.
+fn level_len(num_leaves: u64, level: u32) -> u64 {
+    if level >= u64::BITS {
+        return (num_leaves != 0) as u64;
+    }
+    (num_leaves >> level) + (num_leaves & ((1 << level) - 1) != 0) as u64
+}
+
+fn missing_node(level: u32, index: u64) -> MerkleError {
+    MerkleError::StoreError(format!("missing node at level {}, index {}", level, index))
+}
+
+impl<H, S> LeanMerkleTree<H, S>
+where
+    H: Hasher,
+    S: Store,
+{
+    pub fn new(hasher: H, store: S) -> Self {
+        Self { hasher, store }
+    }
+
+    pub fn add_leaves(&mut self, leaves: &[Node]) -> Result<(), MerkleError> {
+        // Early return
+        if leaves.is_empty() {
+            return Ok(());
+        }
+
+        let start = self.store.get_num_leaves();
+        let Some(num_leaves) = start.checked_add(leaves.len() as u64) else {
+            return Err(MerkleError::TreeFull {
+                depth: u64::BITS,
+                capacity: u64::MAX,
+            });
+        };
+        let depth = depth_for(num_leaves);
+
+        // The only nodes that don't change but are needed are the left siblings of the
+        // first changed node at each level, so fetch them in one batch.
+        let mut levels: Vec<u32> = Vec::with_capacity(depth as usize);
+        let mut indices: Vec<u64> = Vec::with_capacity(depth as usize);
+        for level in 0..depth {
+            let idx = start >> level;
+            if idx & 1 == 1 {
+                levels.push(level);
+                indices.push(idx - 1);
+            }
+        }
+        let mut fetched = self.store.get(&levels, &indices)?.into_iter();
+
+        // Compute the changed nodes level by level, starting at index `lo`.
+        let mut batch: Vec<(u32, u64, Node)> = Vec::with_capacity(leaves.len() * 2 + 64);
+        let mut row: Vec<Node> = leaves.to_vec();
+        let mut lo = start;
+        for level in 0..depth {
+            batch.extend((lo..).zip(&row).map(|(idx, h)| (level, idx, *h)));
+
+            if lo & 1 == 1 {
+                let Some(sib_hash) = fetched.next().flatten() else {
+                    return Err(missing_node(level, lo - 1));
+                };
+                row.insert(0, sib_hash);
+            }
+
+            // A node without a right sibling is carried up without hashing.
+            row = row
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => self.hasher.hash(left, right),
+                    _ => pair[0],
+                })
+                .collect();
+            lo >>= 1;
+        }
+
+        // The root
+        batch.push((depth, 0, row[0]));
+
+        self.store.put(&batch)
+    }
+
+    /// Replaces the leaf at `index` with `leaf` and recomputes the path up to the root.
+    /// All changed nodes are written in a single batch.
+    pub fn update_leaf(&mut self, index: u64, leaf: Node) -> Result<(), MerkleError> {
+        let num_leaves = self.store.get_num_leaves();
+        if index >= num_leaves {
+            return Err(MerkleError::LeafIndexOutOfBounds { index, num_leaves });
+        }
+
+        let (path, _) = self.read_path(index, num_leaves, &[])?;
+
+        let mut batch: Vec<(u32, u64, Node)> = Vec::with_capacity(path.len() + 1);
+        let mut idx = index;
+        let mut h = leaf;
+        for (level, sibling) in path.iter().enumerate() {
+            batch.push((level as u32, idx, h));
+            h = match sibling {
+                Some(sib_hash) if idx & 1 == 1 => self.hasher.hash(sib_hash, &h),
+                Some(sib_hash) => self.hasher.hash(&h, sib_hash),
+                None => h,
+            };
+            idx >>= 1;
+        }
+        batch.push((path.len() as u32, 0, h));
+
+        self.store.put(&batch)
+    }
+
+    pub fn proof(&self, leaf_idx: u64) -> Result<LeanMerkleProof, MerkleError> {
+        let num_leaves = self.store.get_num_leaves();
+        if leaf_idx >= num_leaves {
+            return Err(MerkleError::LeafIndexOutOfBounds {
+                index: leaf_idx,
+                num_leaves,
+            });
+        }
+
+        // Fetch the siblings, the leaf and the root in a single batch.
+        let depth = depth_for(num_leaves);
+        let (path, extra) = self.read_path(leaf_idx, num_leaves, &[(0, leaf_idx), (depth, 0)])?;
+
+        let mut siblings = Vec::with_capacity(path.len());
+        let mut index = 0;
+        for (level, sibling) in path.into_iter().enumerate() {
+            if let Some(sib_hash) = sibling {
+                index |= ((leaf_idx >> level) & 1) << siblings.len();
+                siblings.push(sib_hash);
+            }
+        }
+
+        Ok(LeanMerkleProof {
+            siblings,
+            leaf: extra[0],
+            index,
+            root: extra[1],
+        })
+    }
+
+    // Returns the sibling of the leaf at `index` at each level, or None if it has no
+    // sibling, plus the `extra` nodes. All of them are read in a single batch.
+    #[allow(clippy::type_complexity)]
+    fn read_path(
+        &self,
+        index: u64,
+        num_leaves: u64,
+        extra: &[(u32, u64)],
+    ) -> Result<(Vec<Option<Node>>, Vec<Node>), MerkleError> {
+        let depth = depth_for(num_leaves);
+        let positions: Vec<Option<(u32, u64)>> = (0..depth)
+            .map(|level| {
+                let sibling = (index >> level) ^ 1;
+                (sibling < level_len(num_leaves, level)).then_some((level, sibling))
+            })
+            .collect();
+
+        let (levels, indices): (Vec<u32>, Vec<u64>) =
+            positions.iter().flatten().chain(extra).copied().unzip();
+        let fetched = self.store.get(&levels, &indices)?;
+
+        let mut nodes = Vec::with_capacity(fetched.len());
+        for ((level, idx), node) in levels.iter().zip(&indices).zip(fetched) {
+            nodes.push(node.ok_or_else(|| missing_node(*level, *idx))?);
+        }
+
+        let extra_nodes = nodes.split_off(nodes.len() - extra.len());
+        let mut siblings = nodes.into_iter();
+        let path = positions
+            .iter()
+            .map(|pos| pos.and_then(|_| siblings.next()))
+            .collect();
+
+        Ok((path, extra_nodes))
+    }
+
+    pub fn verify_proof(&self, proof: &LeanMerkleProof) -> bool {
+        proof.verify(&self.hasher)
+    }
+
+    /// Returns the root of the tree, or zero if it's empty.
+    pub fn root(&self) -> Result<Node, MerkleError> {
+        let num_leaves = self.store.get_num_leaves();
+        if num_leaves == 0 {
+            return Ok(Node::ZERO);
+        }
+
+        let depth = depth_for(num_leaves);
+        self.store.get(&[depth], &[0])?[0].ok_or_else(|| missing_node(depth, 0))
+    }
+
+    pub fn depth(&self) -> u32 {
+        depth_for(self.store.get_num_leaves())
+    }
+
+    pub fn num_leaves(&self) -> u64 {
+        self.store.get_num_leaves()
+    }
+}
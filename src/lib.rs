@@ -5,7 +5,8 @@ Merkle tree implementation in Rust with configurable storage backends and hash f
 implementation features:
 
 * Fixed depth: All proofs have a constant size equal to the `Depth`.
-* Append-only: Leaves are added sequentially starting at index `0`. Once added, a leaf cannot be modified.
+* Append-only: Leaves are added sequentially starting at index `0`. Once added, a leaf can be
+  updated in place with `update_leaf`.
 * Optimized for Merkle proof retrieval: Intermediate leaves are stored so that Merkle proofs can be fetched
   from memory without needing to be calculated lazily, resulting in very fast retrieval times.
 
@@ -56,17 +57,31 @@ Note: This example requires the `sled_store` feature to be enabled.
 
 */
 
+pub mod async_tree;
+pub mod dynamic;
 pub mod errors;
+pub mod frontier;
 pub mod hasher;
+pub mod indexed;
+pub mod lean;
+pub mod nary;
 pub mod node;
+pub mod proof;
+pub mod shared;
+pub mod sparse;
 pub mod tree;
+pub mod witness;
 
 pub mod stores {
+    mod async_store;
     #[cfg(feature = "memory_store")]
     mod memory_store;
+    mod mutex_store;
     pub mod store;
+    pub use async_store::{AsyncStore, BlockingStore};
     #[cfg(feature = "memory_store")]
     pub use memory_store::MemoryStore;
+    pub use mutex_store::MutexStore;
     #[cfg(feature = "sled_store")]
     mod sled_store;
     #[cfg(feature = "sled_store")]
@@ -94,3 +109,13 @@ pub use tree::MerkleTree32;
 
 // Re-export the generic MerkleTree for all store types
 pub use tree::MerkleTree;
+
+pub use async_tree::AsyncMerkleTree;
+pub use dynamic::DynMerkleTree;
+pub use frontier::{FrontierParts, FrontierTree};
+pub use indexed::IndexedMerkleTree;
+pub use lean::LeanMerkleTree;
+pub use nary::NaryMerkleTree;
+pub use shared::SharedMerkleTree;
+pub use sparse::SparseMerkleTree;
+pub use witness::WitnessTree;
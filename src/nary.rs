@@ -0,0 +1,212 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! N-ary Merkle tree implementation.
+
+use crate::hasher::Hasher;
+use crate::{MerkleError, Node, Store};
+
+/// Proof of a leaf in a `NaryMerkleTree`, with the `ARITY - 1` siblings of the node at
+/// each level, from left to right and skipping the node itself.
+pub struct NaryMerkleProof<const ARITY: usize, const DEPTH: usize> {
+    pub proof: [Vec<Node>; DEPTH],
+    pub leaf: Node,
+    pub index: u64,
+    pub root: Node,
+}
+
+impl<const ARITY: usize, const DEPTH: usize> NaryMerkleProof<ARITY, DEPTH> {
+    /// Verifies the proof without needing the tree.
+    pub fn verify<H: Hasher>(&self, hasher: &H) -> bool {
+        if ARITY > hasher.max_inputs() {
+            return false;
+        }
+
+        let mut h = self.leaf;
+        let mut idx = self.index;
+        for siblings in &self.proof {
+            if siblings.len() != ARITY - 1 {
+                return false;
+            }
+            let mut children = siblings.clone();
+            children.insert((idx % ARITY as u64) as usize, h);
+            h = hasher.hash_many(&children);
+            idx /= ARITY as u64;
+        }
+        idx == 0 && h == self.root
+    }
+}
+
+/// Fixed-depth append-only Merkle tree where each node has `ARITY` children, hashed with
+/// `Hasher::hash_many`. Since circuits hash several inputs at once for about the same cost
+/// as two, a tree with arity 4 is cheaper to prove than a binary tree with twice the depth.
+/// The leaves are stored at level 0 and the root at level `DEPTH`.
+pub struct NaryMerkleTree<H, S, const ARITY: usize, const DEPTH: usize>
+where
+    H: Hasher,
+    S: Store,
+{
+    hasher: H,
+    store: S,
+    // Root of an empty subtree at each level, from the empty leaf up to the empty root.
+    zeros: Vec<Node>,
+}
+
+impl<H, S, const ARITY: usize, const DEPTH: usize> NaryMerkleTree<H, S, ARITY, DEPTH>
+where
+    H: Hasher,
+    S: Store,
+{
+    /// Creates the tree, panicking if the hasher can't hash `ARITY` nodes. See `try_new`.
+    pub fn new(hasher: H, store: S) -> Self {
+        Self::try_new(hasher, store).expect("failed to create nary merkle tree")
+    }
+
+    /// Creates the tree. Errors if `ARITY` is above `Hasher::max_inputs`, e.g. above 12
+    /// with `PoseidonHasher`.
+    pub fn try_new(hasher: H, store: S) -> Result<Self, MerkleError> {
+        const { assert!(ARITY >= 2, "arity must be at least 2") };
+        const { assert!(DEPTH >= 1, "DEPTH must be at least 1") };
+
+        if ARITY > hasher.max_inputs() {
+            return Err(MerkleError::UnsupportedArity {
+                arity: ARITY,
+                max: hasher.max_inputs(),
+            });
+        }
+
+        let mut zeros = vec![Node::ZERO; DEPTH + 1];
+        for i in 1..=DEPTH {
+            zeros[i] = hasher.hash_many(&vec![zeros[i - 1]; ARITY]);
+        }
+
+        Ok(Self {
+            hasher,
+            store,
+            zeros,
+        })
+    }
+
+    /// Returns the number of leaves that fit in the tree, or None if it doesn't fit in
+    /// a `u64`.
+    fn capacity() -> Option<u64> {
+        (ARITY as u64).checked_pow(DEPTH as u32)
+    }
+
+    pub fn add_leaves(&mut self, leaves: &[Node]) -> Result<(), MerkleError> {
+        // Early return
+        if leaves.is_empty() {
+            return Ok(());
+        }
+
+        // Error if leaves do not fit in the tree
+        let start = self.store.get_num_leaves();
+        let num_leaves = start.checked_add(leaves.len() as u64);
+        if num_leaves.is_none() || Self::capacity().is_some_and(|cap| num_leaves > Some(cap)) {
+            return Err(MerkleError::TreeFull {
+                depth: DEPTH as u32,
+                capacity: Self::capacity().unwrap_or(u64::MAX),
+            });
+        }
+
+        let arity = ARITY as u64;
+
+        // The nodes that don't change but are needed are the ones on the left of the first
+        // changed node in its group at each level, so fetch them in one batch.
+        let mut levels: Vec<u32> = Vec::new();
+        let mut indices: Vec<u64> = Vec::new();
+        let mut lo = start;
+        for level in 0..DEPTH {
+            for idx in lo - lo % arity..lo {
+                levels.push(level as u32);
+                indices.push(idx);
+            }
+            lo /= arity;
+        }
+        let mut fetched = self.store.get(&levels, &indices)?.into_iter();
+
+        // Compute the changed nodes level by level, starting at index `lo`.
+        let mut batch: Vec<(u32, u64, Node)> = Vec::with_capacity(leaves.len() * 2 + DEPTH);
+        let mut row: Vec<Node> = leaves.to_vec();
+        let mut lo = start;
+        for level in 0..DEPTH {
+            batch.extend((lo..).zip(&row).map(|(idx, h)| (level as u32, idx, *h)));
+
+            // Complete the first and last groups with the unchanged and empty nodes.
+            let left = (lo % arity) as usize;
+            let mut children: Vec<Node> = Vec::with_capacity(left + row.len() + ARITY);
+            for _ in 0..left {
+                children.push(fetched.next().flatten().unwrap_or(self.zeros[level]));
+            }
+            children.append(&mut row);
+            let padding = children.len().next_multiple_of(ARITY) - children.len();
+            children.extend(std::iter::repeat_n(self.zeros[level], padding));
+
+            row = children
+                .chunks_exact(ARITY)
+                .map(|group| self.hasher.hash_many(group))
+                .collect();
+            lo /= arity;
+        }
+
+        // The root
+        batch.push((DEPTH as u32, 0, row[0]));
+
+        self.store.put(&batch)
+    }
+
+    pub fn proof(&self, leaf_idx: u64) -> Result<NaryMerkleProof<ARITY, DEPTH>, MerkleError> {
+        if Self::capacity().is_some_and(|cap| leaf_idx >= cap) {
+            return Err(MerkleError::LeafIndexOutOfBounds {
+                index: leaf_idx,
+                num_leaves: Self::capacity().unwrap_or(u64::MAX),
+            });
+        }
+
+        let arity = ARITY as u64;
+
+        // Fetch the siblings, the leaf and the root in a single batch.
+        let mut levels: Vec<u32> = Vec::with_capacity((ARITY - 1) * DEPTH + 2);
+        let mut indices: Vec<u64> = Vec::with_capacity((ARITY - 1) * DEPTH + 2);
+        let mut idx = leaf_idx;
+        for level in 0..DEPTH {
+            let first = idx - idx % arity;
+            for offset in (0..arity).filter(|offset| *offset != idx % arity) {
+                // Only leaf groups can reach past u64::MAX. A leaf can't be stored at
+                // u64::MAX either, so the saturated index reads as empty.
+                levels.push(level as u32);
+                indices.push(first.saturating_add(offset));
+            }
+            idx /= arity;
+        }
+        levels.extend([0, DEPTH as u32]);
+        indices.extend([leaf_idx, 0]);
+
+        let fetched = self.store.get(&levels, &indices)?;
+        let mut nodes = levels
+            .iter()
+            .zip(fetched)
+            .map(|(level, node)| node.unwrap_or(self.zeros[*level as usize]));
+
+        let proof: [Vec<Node>; DEPTH] =
+            std::array::from_fn(|_| nodes.by_ref().take(ARITY - 1).collect());
+
+        Ok(NaryMerkleProof {
+            proof,
+            leaf: nodes.next().expect("leaf is fetched"),
+            index: leaf_idx,
+            root: nodes.next().expect("root is fetched"),
+        })
+    }
+
+    pub fn verify_proof(&self, proof: &NaryMerkleProof<ARITY, DEPTH>) -> bool {
+        proof.verify(&self.hasher)
+    }
+
+    pub fn root(&self) -> Result<Node, MerkleError> {
+        Ok(self.store.get(&[DEPTH as u32], &[0])?[0].unwrap_or(self.zeros[DEPTH]))
+    }
+
+    pub fn num_leaves(&self) -> u64 {
+        self.store.get_num_leaves()
+    }
+}
@@ -1,7 +1,7 @@
 use rand::RngCore;
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Node([u8; 32]);
 
 impl Node {
@@ -0,0 +1,185 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Proofs covering multiple leaves at once.
+
+use crate::hasher::Hasher;
+use crate::tree::Zeros;
+use crate::Node;
+
+/// Proof of multiple leaves against the same root. Instead of one sibling per level and
+/// leaf, it only contains the siblings that can't be computed from the proven leaves.
+pub struct MultiProof<const DEPTH: usize> {
+    /// Sorted and deduplicated indices of the proven leaves.
+    pub indices: Vec<u64>,
+    /// Proven leaves, in the same order as `indices`.
+    pub leaves: Vec<Node>,
+    /// Siblings needed to compute the root, level by level starting at the leaves and
+    /// sorted by index within each level.
+    pub proof: Vec<Node>,
+    pub root: Node,
+}
+
+impl<const DEPTH: usize> MultiProof<DEPTH> {
+    /// Verifies the proof without needing the tree. Returns false if the proof is
+    /// malformed or if the computed root doesn't match.
+    pub fn verify<H: Hasher>(&self, hasher: &H) -> bool {
+        if self.indices.is_empty()
+            || self.indices.len() != self.leaves.len()
+            || self.indices.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            return false;
+        }
+
+        // Known nodes at the current level, sorted by index.
+        let mut nodes: Vec<(u64, Node)> = self
+            .indices
+            .iter()
+            .copied()
+            .zip(self.leaves.iter().copied())
+            .collect();
+        let mut proof = self.proof.iter();
+
+        for _ in 0..DEPTH {
+            let mut parents: Vec<(u64, Node)> = Vec::with_capacity(nodes.len());
+            let mut i = 0;
+            while i < nodes.len() {
+                let (idx, h) = nodes[i];
+
+                // Both children are known, otherwise the sibling comes from the proof.
+                let (left, right) =
+                    if idx & 1 == 0 && nodes.get(i + 1).map(|n| n.0) == Some(idx + 1) {
+                        i += 1;
+                        (h, nodes[i].1)
+                    } else {
+                        let Some(sib_hash) = proof.next() else {
+                            return false;
+                        };
+                        if idx & 1 == 1 {
+                            (*sib_hash, h)
+                        } else {
+                            (h, *sib_hash)
+                        }
+                    };
+
+                parents.push((idx >> 1, hasher.hash(&left, &right)));
+                i += 1;
+            }
+            nodes = parents;
+        }
+
+        proof.next().is_none() && nodes == [(0, self.root)]
+    }
+}
+
+/// Proof that the leaves in the range `[start, end)` are exactly a given list. Since the
+/// nodes inside the range are computed from the leaves, it only contains the siblings on
+/// the left and right boundaries of the range at each level.
+pub struct RangeProof<const DEPTH: usize> {
+    pub start: u64,
+    pub end: u64,
+    /// Siblings on the left of the range, from the leaves up. There is one for each level
+    /// where the range starts at an odd index.
+    pub left: Vec<Node>,
+    /// Siblings on the right of the range, from the leaves up. There is one for each level
+    /// where the range ends at an even index.
+    pub right: Vec<Node>,
+    pub root: Node,
+}
+
+impl<const DEPTH: usize> RangeProof<DEPTH> {
+    /// Verifies that `leaves` are the leaves in the range without needing the tree. Returns
+    /// false if the proof is malformed or if the computed root doesn't match.
+    pub fn verify<H: Hasher>(&self, hasher: &H, leaves: &[Node]) -> bool {
+        if self.start >= self.end || leaves.len() as u64 != self.end - self.start {
+            return false;
+        }
+
+        let mut left = self.left.iter();
+        let mut right = self.right.iter();
+
+        // Nodes in the range at the current level, starting at index `lo`.
+        let mut row: Vec<Node> = leaves.to_vec();
+        let mut lo = self.start;
+
+        for _ in 0..DEPTH {
+            let hi = lo + row.len() as u64 - 1;
+
+            // Complete the first and last pairs with the boundary siblings.
+            let first = if lo & 1 == 1 {
+                let Some(sib_hash) = left.next() else {
+                    return false;
+                };
+                Some(*sib_hash)
+            } else {
+                None
+            };
+            let last = if hi & 1 == 0 {
+                let Some(sib_hash) = right.next() else {
+                    return false;
+                };
+                Some(*sib_hash)
+            } else {
+                None
+            };
+
+            let padded: Vec<Node> = first.into_iter().chain(row).chain(last).collect();
+            row = padded
+                .chunks_exact(2)
+                .map(|pair| hasher.hash(&pair[0], &pair[1]))
+                .collect();
+            lo >>= 1;
+        }
+
+        left.next().is_none() && right.next().is_none() && row == [self.root]
+    }
+}
+
+/// Proof that the tree with `new_size` leaves extends the tree with `old_size` leaves
+/// without modifying any of its leaves. It's the path of the leaf at index `old_size` in the
+/// new tree: its siblings on the left are the same in both trees, so they are used to
+/// compute both roots.
+pub struct ConsistencyProof<const DEPTH: usize> {
+    pub old_size: u64,
+    pub new_size: u64,
+    /// Siblings of the leaf at index `old_size` in the new tree, from the leaves up.
+    pub proof: [Node; DEPTH],
+    /// Leaf at index `old_size` in the new tree.
+    pub leaf: Node,
+}
+
+impl<const DEPTH: usize> ConsistencyProof<DEPTH> {
+    /// Verifies that `new_root` is the root of a tree extending the tree of `old_root`,
+    /// without needing the tree. Returns false if the sizes are not consistent or if any of
+    /// the computed roots doesn't match.
+    pub fn verify<H: Hasher>(&self, hasher: &H, old_root: &Node, new_root: &Node) -> bool {
+        if self.old_size > self.new_size {
+            return false;
+        }
+        if self.old_size == self.new_size {
+            return old_root == new_root;
+        }
+
+        let zeros = Zeros::<DEPTH>::new(hasher);
+        let mut old_hash = zeros[0];
+        let mut new_hash = self.leaf;
+        let mut idx = self.old_size;
+
+        for (level, sib_hash) in self.proof.iter().enumerate() {
+            if idx & 1 == 1 {
+                old_hash = hasher.hash(sib_hash, &old_hash);
+                new_hash = hasher.hash(sib_hash, &new_hash);
+            } else {
+                // Siblings starting at or beyond the new size must be empty.
+                if idx + 1 > (self.new_size - 1) >> level && *sib_hash != zeros[level] {
+                    return false;
+                }
+                old_hash = hasher.hash(&old_hash, &zeros[level]);
+                new_hash = hasher.hash(&new_hash, sib_hash);
+            }
+            idx >>= 1;
+        }
+
+        // The old size must fit in the tree.
+        idx == 0 && old_hash == *old_root && new_hash == *new_root
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Merkle tree that can be shared between threads.
+
+use crate::hasher::Hasher;
+use crate::tree::{MerkleProof, MerkleTree};
+use crate::{MerkleError, Node, Store};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Cloneable handle to a `MerkleTree` shared between threads. Any number of readers run in
+/// parallel, while writers are serialized and wait for the ongoing reads to finish, so a
+/// read always sees the tree either before or after a whole write. It's `Send` and `Sync`
+/// as long as the hasher and the store are. Stores that are only `Send`, like
+/// `SqliteStore`, can be wrapped in a `MutexStore`.
+///
+/// If a writer panics, the tree may be left with only part of its changes, so the other
+/// handles stop using it: `read` and `write` panic, and the other methods return
+/// `MerkleError::LockPoisoned`.
+pub struct SharedMerkleTree<H, S, const DEPTH: usize>
+where
+    H: Hasher,
+    S: Store,
+{
+    tree: Arc<RwLock<MerkleTree<H, S, DEPTH>>>,
+}
+
+impl<H, S, const DEPTH: usize> Clone for SharedMerkleTree<H, S, DEPTH>
+where
+    H: Hasher,
+    S: Store,
+{
+    fn clone(&self) -> Self {
+        Self {
+            tree: Arc::clone(&self.tree),
+        }
+    }
+}
+
+impl<H, S, const DEPTH: usize> From<MerkleTree<H, S, DEPTH>> for SharedMerkleTree<H, S, DEPTH>
+where
+    H: Hasher,
+    S: Store,
+{
+    fn from(tree: MerkleTree<H, S, DEPTH>) -> Self {
+        Self::new(tree)
+    }
+}
+
+impl<H, S, const DEPTH: usize> SharedMerkleTree<H, S, DEPTH>
+where
+    H: Hasher,
+    S: Store,
+{
+    pub fn new(tree: MerkleTree<H, S, DEPTH>) -> Self {
+        Self {
+            tree: Arc::new(RwLock::new(tree)),
+        }
+    }
+
+    // A closure passed to `write` may make several writes, so a panic in between would
+    // leave the tree half updated. The poisoning is propagated rather than ignored.
+    fn read_lock(&self) -> Result<RwLockReadGuard<'_, MerkleTree<H, S, DEPTH>>, MerkleError> {
+        self.tree.read().map_err(|_| MerkleError::LockPoisoned)
+    }
+
+    fn write_lock(&self) -> Result<RwLockWriteGuard<'_, MerkleTree<H, S, DEPTH>>, MerkleError> {
+        self.tree.write().map_err(|_| MerkleError::LockPoisoned)
+    }
+
+    /// Runs `f` with shared access to the tree. Calls made within `f` see the same version
+    /// of the tree, e.g. to get several proofs against the same root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a writer panicked while holding the lock.
+    pub fn read<R>(&self, f: impl FnOnce(&MerkleTree<H, S, DEPTH>) -> R) -> R {
+        f(&self.read_lock().expect("merkle tree lock poisoned"))
+    }
+
+    /// Runs `f` with exclusive access to the tree, e.g. to call `update_leaf` or `truncate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a writer panicked while holding the lock.
+    pub fn write<R>(&self, f: impl FnOnce(&mut MerkleTree<H, S, DEPTH>) -> R) -> R {
+        f(&mut self.write_lock().expect("merkle tree lock poisoned"))
+    }
+
+    pub fn add_leaves(&self, leaves: &[Node]) -> Result<(), MerkleError> {
+        self.write_lock()?.add_leaves(leaves)
+    }
+
+    pub fn root(&self) -> Result<Node, MerkleError> {
+        self.read_lock()?.root()
+    }
+
+    /// Returns the proof of `leaf_idx`. The siblings, the leaf and the root are read in a
+    /// single batch under the read lock, so they belong to the same version of the tree.
+    pub fn proof(&self, leaf_idx: u64) -> Result<MerkleProof<DEPTH>, MerkleError> {
+        self.read_lock()?.proof(leaf_idx)
+    }
+
+    pub fn num_leaves(&self) -> Result<u64, MerkleError> {
+        Ok(self.read_lock()?.num_leaves())
+    }
+
+    pub fn verify_proof(&self, proof: &MerkleProof<DEPTH>) -> Result<bool, MerkleError> {
+        self.read_lock()?.verify_proof(proof)
+    }
+}
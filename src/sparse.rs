@@ -0,0 +1,334 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Sparse Merkle tree implementation.
+
+use crate::hasher::Hasher;
+use crate::tree::Zeros;
+use crate::{MerkleError, Node, Store};
+
+/// Depth of the sparse tree, one level per bit of the key.
+const SPARSE_DEPTH: usize = 256;
+
+const KEY_NUM_LEAVES: &str = "SMT_NUM_LEAVES";
+
+// Returns the metadata key of the node at `level` covering `prefix`.
+fn node_key(level: usize, prefix: &Node) -> String {
+    format!("SMT_{}_{}", level, prefix)
+}
+
+// Returns `key` with its `level` least significant bits cleared, which identifies the node
+// at `level` on the path of `key`.
+fn prefix(key: &Node, level: usize) -> Node {
+    let mut bytes: [u8; Node::LEN] = key.as_ref().try_into().expect("node is 32 bytes");
+    if level >= SPARSE_DEPTH {
+        return Node::ZERO;
+    }
+    let full_bytes = level / 8;
+    bytes[Node::LEN - full_bytes..].fill(0);
+    bytes[Node::LEN - 1 - full_bytes] &= !((1u8 << (level % 8)) - 1);
+    Node::from(bytes)
+}
+
+// Returns true if the node at `level` on the path of `key` is a right child.
+fn is_right(key: &Node, level: usize) -> bool {
+    key.as_ref()[Node::LEN - 1 - level / 8] & (1 << (level % 8)) != 0
+}
+
+// Returns the prefix of the sibling of the node at `level` on the path of `key`.
+fn sibling_prefix(key: &Node, level: usize) -> Node {
+    let mut bytes: [u8; Node::LEN] = prefix(key, level)
+        .as_ref()
+        .try_into()
+        .expect("node is 32 bytes");
+    bytes[Node::LEN - 1 - level / 8] ^= 1 << (level % 8);
+    Node::from(bytes)
+}
+
+/// Proof that `key` has `value` in a `SparseMerkleTree`, or that it's not in the tree if
+/// `value` is None. Only the siblings that are not empty subtrees are included.
+pub struct SparseMerkleProof {
+    pub key: Node,
+    pub value: Option<Node>,
+    /// Levels and hashes of the non-empty siblings, from the leaves up.
+    pub siblings: Vec<(u32, Node)>,
+    pub root: Node,
+}
+
+impl SparseMerkleProof {
+    /// Verifies the proof without needing the tree. Returns false if the proof is
+    /// malformed or if the computed root doesn't match.
+    pub fn verify<H: Hasher>(&self, hasher: &H) -> bool {
+        if self.siblings.windows(2).any(|pair| pair[0].0 >= pair[1].0)
+            || self.value == Some(Node::ZERO)
+        {
+            return false;
+        }
+
+        let zeros = Zeros::<SPARSE_DEPTH>::new(hasher);
+        let mut siblings = self.siblings.iter().peekable();
+        let mut h = self.value.unwrap_or(zeros[0]);
+        for level in 0..SPARSE_DEPTH {
+            let sib_hash = match siblings.next_if(|(l, _)| *l as usize == level) {
+                Some((_, sib_hash)) => *sib_hash,
+                None => zeros[level],
+            };
+            h = if is_right(&self.key, level) {
+                hasher.hash(&sib_hash, &h)
+            } else {
+                hasher.hash(&h, &sib_hash)
+            };
+        }
+
+        siblings.next().is_none() && h == self.root
+    }
+}
+
+/// Merkle tree of depth 256 where each leaf is placed at the path given by the bits of its
+/// key, from the most significant bit at the root. Empty subtrees are not stored but
+/// replaced with precomputed zeros, so it can prove that a key is not in the tree, which
+/// an append-only tree can't.
+///
+/// The leaf of a key is its value, and empty leaves are zero, so zero values are not
+/// allowed. Since the store indexes nodes with `u64`, which can't hold 256-bit paths,
+/// nodes are stored as metadata keyed by their level and path.
+pub struct SparseMerkleTree<H, S>
+where
+    H: Hasher,
+    S: Store,
+{
+    hasher: H,
+    store: S,
+    zeros: Zeros<SPARSE_DEPTH>,
+}
+
+impl<H, S> SparseMerkleTree<H, S>
+where
+    H: Hasher,
+    S: Store,
+{
+    pub fn new(hasher: H, store: S) -> Self {
+        let zeros = Zeros::new(&hasher);
+        Self {
+            hasher,
+            store,
+            zeros,
+        }
+    }
+
+    /// Returns the value of `key`, or None if it's not in the tree.
+    pub fn get(&self, key: &Node) -> Result<Option<Node>, MerkleError> {
+        let leaf = self.read_node(0, key)?;
+        Ok((leaf != self.zeros[0]).then_some(leaf))
+    }
+
+    /// Inserts `key` with `value`. Errors if the key is already in the tree.
+    pub fn insert(&mut self, key: &Node, value: Node) -> Result<(), MerkleError> {
+        if value == self.zeros[0] {
+            return Err(MerkleError::ZeroValue { key: *key });
+        }
+        if self.get(key)?.is_some() {
+            return Err(MerkleError::KeyExists { key: *key });
+        }
+        self.set(key, value, self.num_leaves()? + 1)
+    }
+
+    /// Replaces the value of `key`. Errors if the key is not in the tree.
+    pub fn update(&mut self, key: &Node, value: Node) -> Result<(), MerkleError> {
+        if value == self.zeros[0] {
+            return Err(MerkleError::ZeroValue { key: *key });
+        }
+        if self.get(key)?.is_none() {
+            return Err(MerkleError::KeyNotFound { key: *key });
+        }
+        self.set(key, value, self.num_leaves()?)
+    }
+
+    /// Removes `key` from the tree. Errors if the key is not in the tree.
+    pub fn delete(&mut self, key: &Node) -> Result<(), MerkleError> {
+        if self.get(key)?.is_none() {
+            return Err(MerkleError::KeyNotFound { key: *key });
+        }
+        let zero = self.zeros[0];
+        self.set(key, zero, self.num_leaves()? - 1)
+    }
+
+    // Writes `leaf` at the path of `key` and recomputes the path up to the root, along
+    // with the new number of leaves, in a single batch. The siblings are read at once.
+    fn set(&mut self, key: &Node, leaf: Node, num_leaves: u64) -> Result<(), MerkleError> {
+        let siblings =
+            self.read_nodes((0..SPARSE_DEPTH).map(|level| (level, sibling_prefix(key, level))))?;
+
+        let mut path: Vec<(String, Vec<u8>)> = Vec::with_capacity(SPARSE_DEPTH + 2);
+        let mut h = leaf;
+        for (level, sib_hash) in siblings.iter().enumerate() {
+            path.push((
+                node_key(level, &prefix(key, level)),
+                self.encode_node(level, &h),
+            ));
+
+            h = if is_right(key, level) {
+                self.hasher.hash(sib_hash, &h)
+            } else {
+                self.hasher.hash(&h, sib_hash)
+            };
+        }
+        path.push((
+            node_key(SPARSE_DEPTH, &Node::ZERO),
+            self.encode_node(SPARSE_DEPTH, &h),
+        ));
+        path.push((
+            KEY_NUM_LEAVES.to_string(),
+            num_leaves.to_be_bytes().to_vec(),
+        ));
+
+        let metadata: Vec<(&str, &[u8])> = path
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_slice()))
+            .collect();
+        self.store.put_with_metadata(&[], &metadata)
+    }
+
+    // Empty subtrees are written as an empty value, which removes them from the store.
+    fn encode_node(&self, level: usize, node: &Node) -> Vec<u8> {
+        if *node == self.zeros[level] {
+            Vec::new()
+        } else {
+            node.as_ref().to_vec()
+        }
+    }
+
+    // Reads the node at `level` on the path of `key`.
+    fn read_node(&self, level: usize, key: &Node) -> Result<Node, MerkleError> {
+        Ok(self.read_nodes([(level, *key)])?[0])
+    }
+
+    // Reads the nodes at each `(level, key)`, each one on the path of its key, at once.
+    fn read_nodes(
+        &self,
+        nodes: impl IntoIterator<Item = (usize, Node)>,
+    ) -> Result<Vec<Node>, MerkleError> {
+        let (levels, keys): (Vec<usize>, Vec<String>) = nodes
+            .into_iter()
+            .map(|(level, key)| (level, node_key(level, &prefix(&key, level))))
+            .unzip();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        self.store
+            .get_metadata_many(&keys)?
+            .into_iter()
+            .zip(levels)
+            .map(|(bytes, level)| match bytes {
+                Some(bytes) => {
+                    let bytes: [u8; Node::LEN] = bytes
+                        .as_slice()
+                        .try_into()
+                        .map_err(|_| MerkleError::StoreError("invalid node length".into()))?;
+                    Ok(Node::from(bytes))
+                }
+                None => Ok(self.zeros[level]),
+            })
+            .collect()
+    }
+
+    /// Returns the proof that `key` has its current value, or that it's not in the tree.
+    pub fn proof(&self, key: &Node) -> Result<SparseMerkleProof, MerkleError> {
+        // The siblings, then the leaf and the root, read at once.
+        let mut nodes = self.read_nodes(
+            (0..SPARSE_DEPTH)
+                .map(|level| (level, sibling_prefix(key, level)))
+                .chain([(0, *key), (SPARSE_DEPTH, Node::ZERO)]),
+        )?;
+        let root = nodes.pop().expect("root was read");
+        let leaf = nodes.pop().expect("leaf was read");
+
+        let siblings = nodes
+            .into_iter()
+            .enumerate()
+            .filter(|(level, sib_hash)| *sib_hash != self.zeros[*level])
+            .map(|(level, sib_hash)| (level as u32, sib_hash))
+            .collect();
+
+        Ok(SparseMerkleProof {
+            key: *key,
+            value: (leaf != self.zeros[0]).then_some(leaf),
+            siblings,
+            root,
+        })
+    }
+
+    pub fn verify_proof(&self, proof: &SparseMerkleProof) -> bool {
+        proof.verify(&self.hasher)
+    }
+
+    pub fn root(&self) -> Result<Node, MerkleError> {
+        self.read_node(SPARSE_DEPTH, &Node::ZERO)
+    }
+
+    /// Returns the number of keys in the tree.
+    pub fn num_leaves(&self) -> Result<u64, MerkleError> {
+        self.store
+            .get_metadata(KEY_NUM_LEAVES)?
+            .map(|bytes| {
+                let bytes: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| MerkleError::StoreError("invalid num_leaves length".into()))?;
+                Ok(u64::from_be_bytes(bytes))
+            })
+            .unwrap_or(Ok(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::to_node;
+
+    #[cfg(feature = "memory_store")]
+    use crate::{hasher::Keccak256Hasher, stores::MemoryStore};
+
+    #[test]
+    fn test_prefix() {
+        let key = to_node!("0xff000000000000000000000000000000000000000000000000000000000000ff");
+        assert_eq!(prefix(&key, 0), key);
+        assert_eq!(
+            prefix(&key, 3),
+            to_node!("0xff000000000000000000000000000000000000000000000000000000000000f8")
+        );
+        assert_eq!(
+            prefix(&key, 255),
+            to_node!("0x8000000000000000000000000000000000000000000000000000000000000000")
+        );
+        assert_eq!(prefix(&key, 256), Node::ZERO);
+        assert_eq!(
+            sibling_prefix(&key, 8),
+            to_node!("0xff00000000000000000000000000000000000000000000000000000000000100")
+        );
+        assert!(is_right(&key, 0));
+        assert!(!is_right(&key, 8));
+        assert!(is_right(&key, 255));
+    }
+
+    #[cfg(feature = "memory_store")]
+    #[test]
+    fn test_sparse_removes_empty_subtrees() {
+        let mut tree = SparseMerkleTree::new(Keccak256Hasher, MemoryStore::new());
+        let key = to_node!("0x0000000000000000000000000000000000000000000000000000000000000003");
+        let value = to_node!("0x0000000000000000000000000000000000000000000000000000000000000011");
+
+        tree.insert(&key, value).unwrap();
+        let root_key = node_key(SPARSE_DEPTH, &Node::ZERO);
+        assert!(tree
+            .store
+            .get_metadata(&node_key(0, &key))
+            .unwrap()
+            .is_some());
+        assert!(tree.store.get_metadata(&root_key).unwrap().is_some());
+
+        tree.delete(&key).unwrap();
+        for level in 0..=SPARSE_DEPTH {
+            let key = node_key(level, &prefix(&key, level));
+            assert_eq!(tree.store.get_metadata(&key).unwrap(), None);
+        }
+    }
+}
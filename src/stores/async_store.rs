@@ -0,0 +1,107 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Async version of the store trait, plus an adapter to use any blocking store with it.
+
+use crate::{errors::MerkleError, node::Node, Store};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Same as `Store` but with async methods, so that stores doing disk or network I/O don't
+/// block the executor threads. Arguments are owned so that they can be moved to other
+/// tasks. See `Store` for the semantics of each method.
+pub trait AsyncStore: Send + Sync {
+    fn get(
+        &self,
+        levels: Vec<u32>,
+        indices: Vec<u64>,
+    ) -> impl Future<Output = Result<Vec<Option<Node>>, MerkleError>> + Send;
+
+    fn put(
+        &mut self,
+        items: Vec<(u32, u64, Node)>,
+    ) -> impl Future<Output = Result<(), MerkleError>> + Send {
+        self.put_with_metadata(items, Vec::new())
+    }
+
+    fn put_with_metadata(
+        &mut self,
+        items: Vec<(u32, u64, Node)>,
+        metadata: Vec<(String, Vec<u8>)>,
+    ) -> impl Future<Output = Result<(), MerkleError>> + Send;
+
+    fn get_metadata(
+        &self,
+        key: String,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, MerkleError>> + Send;
+
+    fn get_num_leaves(&self) -> impl Future<Output = u64> + Send;
+}
+
+/// Adapts a blocking `Store`, such as `SledStore`, `RocksDbStore` or `SqliteStore`, to
+/// `AsyncStore` by running its calls on the tokio blocking thread pool with
+/// `spawn_blocking`. Calls are serialized, since stores like `SqliteStore` can't be used
+/// from several threads at once. Requires a tokio runtime.
+pub struct BlockingStore<S: Store> {
+    store: Arc<Mutex<S>>,
+}
+
+impl<S> BlockingStore<S>
+where
+    S: Store + Send + 'static,
+{
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(store)),
+        }
+    }
+
+    /// Runs `f` with the store on the blocking thread pool.
+    async fn run<R, F>(&self, f: F) -> Result<R, MerkleError>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut S) -> Result<R, MerkleError> + Send + 'static,
+    {
+        let mut store = Arc::clone(&self.store).lock_owned().await;
+        tokio::task::spawn_blocking(move || f(&mut store))
+            .await
+            .map_err(|e| MerkleError::StoreError(e.to_string()))?
+    }
+}
+
+impl<S> AsyncStore for BlockingStore<S>
+where
+    S: Store + Send + 'static,
+{
+    async fn get(
+        &self,
+        levels: Vec<u32>,
+        indices: Vec<u64>,
+    ) -> Result<Vec<Option<Node>>, MerkleError> {
+        self.run(move |store| store.get(&levels, &indices)).await
+    }
+
+    async fn put_with_metadata(
+        &mut self,
+        items: Vec<(u32, u64, Node)>,
+        metadata: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), MerkleError> {
+        self.run(move |store| {
+            let metadata: Vec<(&str, &[u8])> = metadata
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_slice()))
+                .collect();
+            store.put_with_metadata(&items, &metadata)
+        })
+        .await
+    }
+
+    async fn get_metadata(&self, key: String) -> Result<Option<Vec<u8>>, MerkleError> {
+        self.run(move |store| store.get_metadata(&key)).await
+    }
+
+    async fn get_num_leaves(&self) -> u64 {
+        // The stores keep the number of leaves in memory, so there is no need to block.
+        self.store.lock().await.get_num_leaves()
+    }
+}
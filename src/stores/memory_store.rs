@@ -2,6 +2,7 @@
 
 //! Simple in-memory store implementation.
 
+use crate::store::{first_truncated_index, next_num_leaves};
 use crate::{MerkleError, Node, Store};
 use std::collections::HashMap;
 
@@ -9,6 +10,7 @@ use std::collections::HashMap;
 #[derive(Default)]
 pub struct MemoryStore {
     store: HashMap<(u32, u64), Node>,
+    metadata: HashMap<String, Vec<u8>>,
     num_leaves: u64,
 }
 
@@ -38,14 +40,47 @@ impl Store for MemoryStore {
         Ok(result)
     }
 
-    fn put(&mut self, items: &[(u32, u64, Node)]) -> Result<(), MerkleError> {
+    fn get_range(&self, level: u32, start: u64, end: u64) -> Result<Vec<(u64, Node)>, MerkleError> {
+        // The map is not sorted, so just get all the indexes in the range one by one.
+        Ok((start..end)
+            .filter_map(|idx| self.store.get(&(level, idx)).map(|node| (idx, *node)))
+            .collect())
+    }
+
+    fn put_with_metadata(
+        &mut self,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
         for (level, index, hash) in items {
             self.store.insert((*level, *index), *hash);
         }
-        let counter = items.iter().filter(|(level, _, _)| *level == 0).count();
-        self.num_leaves += counter as u64;
+        for (key, value) in metadata {
+            if value.is_empty() {
+                self.metadata.remove(*key);
+            } else {
+                self.metadata.insert(key.to_string(), value.to_vec());
+            }
+        }
+        self.num_leaves = next_num_leaves(self.num_leaves, items);
         Ok(())
     }
+
+    fn truncate(
+        &mut self,
+        num_leaves: u64,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
+        self.store
+            .retain(|&(level, index), _| index < first_truncated_index(num_leaves, level));
+        self.num_leaves = num_leaves;
+        self.put_with_metadata(items, metadata)
+    }
+
+    fn get_metadata(&self, key: &str) -> Result<Option<Vec<u8>>, MerkleError> {
+        Ok(self.metadata.get(key).cloned())
+    }
     fn get_num_leaves(&self) -> u64 {
         self.num_leaves
     }
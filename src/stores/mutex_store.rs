@@ -0,0 +1,77 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Adapter to share a store that is not `Sync` between threads.
+
+use crate::{errors::MerkleError, node::Node, Store};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Wraps a store that is `Send` but not `Sync`, such as `SqliteStore`, so that it can be
+/// read from several threads, e.g. in a `SharedMerkleTree`. Reads are serialized by a
+/// mutex, while writes already have exclusive access and don't lock.
+pub struct MutexStore<S: Store> {
+    store: Mutex<S>,
+}
+
+impl<S: Store> MutexStore<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Mutex::new(store),
+        }
+    }
+
+    /// Returns the wrapped store.
+    pub fn into_inner(self) -> S {
+        self.store
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Only reads take the lock, so a panicking reader can't leave the store half updated
+    // and the lock can be used even if poisoned.
+    fn lock(&self) -> MutexGuard<'_, S> {
+        self.store.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get_mut(&mut self) -> &mut S {
+        self.store.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<S: Store> Store for MutexStore<S> {
+    fn get(&self, levels: &[u32], indices: &[u64]) -> Result<Vec<Option<Node>>, MerkleError> {
+        self.lock().get(levels, indices)
+    }
+
+    fn get_range(&self, level: u32, start: u64, end: u64) -> Result<Vec<(u64, Node)>, MerkleError> {
+        self.lock().get_range(level, start, end)
+    }
+
+    fn put_with_metadata(
+        &mut self,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
+        self.get_mut().put_with_metadata(items, metadata)
+    }
+
+    fn truncate(
+        &mut self,
+        num_leaves: u64,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
+        self.get_mut().truncate(num_leaves, items, metadata)
+    }
+
+    fn get_metadata(&self, key: &str) -> Result<Option<Vec<u8>>, MerkleError> {
+        self.lock().get_metadata(key)
+    }
+
+    fn get_metadata_many(&self, keys: &[&str]) -> Result<Vec<Option<Vec<u8>>>, MerkleError> {
+        self.lock().get_metadata_many(keys)
+    }
+
+    fn get_num_leaves(&self) -> u64 {
+        self.lock().get_num_leaves()
+    }
+}
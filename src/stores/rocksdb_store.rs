@@ -2,6 +2,8 @@
 
 //! RocksDB store implementation.
 
+#[cfg(feature = "rocksdb_store")]
+use crate::store::{decode_num_leaves, first_truncated_index, next_num_leaves, MAX_LEVEL};
 #[cfg(feature = "rocksdb_store")]
 use crate::{MerkleError, Node, Store};
 
@@ -26,6 +28,25 @@ impl RocksDbStore {
         key
     }
 
+    fn decode_index(key: &[u8]) -> Result<u64, MerkleError> {
+        let bytes: [u8; 8] = key
+            .get(4..)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| MerkleError::StoreError("invalid key length".into()))?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    // Adds the metadata to the batch, removing the keys with an empty value.
+    fn write_metadata(batch: &mut rocksdb::WriteBatch, metadata: &[(&str, &[u8])]) {
+        for (key, value) in metadata {
+            if value.is_empty() {
+                batch.delete(key.as_bytes());
+            } else {
+                batch.put(key.as_bytes(), value);
+            }
+        }
+    }
+
     fn decode_node(bytes: &[u8]) -> Result<Node, MerkleError> {
         let arr: [u8; Node::LEN] = bytes
             .try_into()
@@ -33,28 +54,26 @@ impl RocksDbStore {
         Ok(Node::from(arr))
     }
 
+    /// Opens the RocksDB database at `path`, panicking on failure. See `try_new`.
     pub fn new(path: &str) -> Self {
+        Self::try_new(path).expect("failed to open rocksdb store")
+    }
+
+    /// Opens the RocksDB database at `path`, or creates it if it doesn't exist.
+    pub fn try_new(path: &str) -> Result<Self, MerkleError> {
         use rocksdb::{Options, DB};
         let mut opts = Options::default();
         opts.create_if_missing(true);
-        let db = DB::open(&opts, path).expect("failed to open rocksdb");
+        let db = DB::open(&opts, path).map_err(|e| MerkleError::StoreOpenError(e.to_string()))?;
 
         let num_leaves = db
             .get(Self::KEY_NUM_LEAVES)
-            .expect("failed to get num_leaves")
-            .map(|v| {
-                let slice: &[u8] = &v;
-                let bytes: [u8; 8] = slice.try_into().expect("invalid num_leaves length");
-                u64::from_be_bytes(bytes)
-            })
+            .map_err(|e| MerkleError::StoreInitError(e.to_string()))?
+            .map(|v| decode_num_leaves(&v))
+            .transpose()?
             .unwrap_or(0);
 
-        if num_leaves == 0 {
-            db.flush().expect("failed to flush");
-            // TODO: unsure if ok
-        }
-
-        Self { db, num_leaves }
+        Ok(Self { db, num_leaves })
     }
 }
 
@@ -90,7 +109,30 @@ impl Store for RocksDbStore {
         result
     }
 
-    fn put(&mut self, items: &[(u32, u64, Node)]) -> Result<(), MerkleError> {
+    fn get_range(&self, level: u32, start: u64, end: u64) -> Result<Vec<(u64, Node)>, MerkleError> {
+        use rocksdb::{Direction, IteratorMode, ReadOptions};
+
+        // Keys are sorted by level and then by index, so the range is a single scan.
+        let start_key = Self::encode_key(level, start);
+        let end_key = Self::encode_key(level, end);
+
+        let mut opts = ReadOptions::default();
+        opts.set_iterate_upper_bound(end_key.to_vec());
+
+        self.db
+            .iterator_opt(IteratorMode::From(&start_key, Direction::Forward), opts)
+            .map(|item| {
+                let (key, value) = item.map_err(Self::db_error)?;
+                Ok((Self::decode_index(&key)?, Self::decode_node(&value)?))
+            })
+            .collect()
+    }
+
+    fn put_with_metadata(
+        &mut self,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
         use rocksdb::WriteBatch;
         let mut batch = WriteBatch::default();
 
@@ -99,8 +141,11 @@ impl Store for RocksDbStore {
             batch.put(key, node.as_ref());
         }
 
-        let counter = items.iter().filter(|(level, _, _)| *level == 0).count() as u64;
-        let new_leaves = self.num_leaves + counter;
+        // Metadata keys are ascii strings so they never collide with the node keys,
+        // which always start with the big-endian level.
+        Self::write_metadata(&mut batch, metadata);
+
+        let new_leaves = next_num_leaves(self.num_leaves, items);
         batch.put(Self::KEY_NUM_LEAVES, new_leaves.to_be_bytes().as_ref());
 
         self.db.write(batch).map_err(Self::db_error)?;
@@ -108,6 +153,49 @@ impl Store for RocksDbStore {
         Ok(())
     }
 
+    fn truncate(
+        &mut self,
+        num_leaves: u64,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
+        use rocksdb::WriteBatch;
+        let mut batch = WriteBatch::default();
+
+        // Keys are sorted by level and then by index, so each level is a single range.
+        for level in 0..=MAX_LEVEL {
+            let start = Self::encode_key(level, first_truncated_index(num_leaves, level));
+            let end = Self::encode_key(level + 1, 0);
+            batch.delete_range(start, end);
+        }
+
+        for (level, index, node) in items {
+            let key = Self::encode_key(*level, *index);
+            batch.put(key, node.as_ref());
+        }
+
+        Self::write_metadata(&mut batch, metadata);
+
+        let new_leaves = next_num_leaves(num_leaves, items);
+        batch.put(Self::KEY_NUM_LEAVES, new_leaves.to_be_bytes().as_ref());
+
+        self.db.write(batch).map_err(Self::db_error)?;
+        self.num_leaves = new_leaves;
+        Ok(())
+    }
+
+    fn get_metadata(&self, key: &str) -> Result<Option<Vec<u8>>, MerkleError> {
+        self.db.get(key.as_bytes()).map_err(Self::db_error)
+    }
+
+    fn get_metadata_many(&self, keys: &[&str]) -> Result<Vec<Option<Vec<u8>>>, MerkleError> {
+        self.db
+            .multi_get(keys.iter().map(|key| key.as_bytes()))
+            .into_iter()
+            .map(|res| res.map_err(Self::db_error))
+            .collect()
+    }
+
     fn get_num_leaves(&self) -> u64 {
         self.num_leaves
     }
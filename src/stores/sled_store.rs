@@ -2,6 +2,8 @@
 
 //! Sled store implementation.
 
+#[cfg(feature = "sled_store")]
+use crate::store::{decode_num_leaves, first_truncated_index, next_num_leaves, MAX_LEVEL};
 #[cfg(feature = "sled_store")]
 use crate::{MerkleError, Node, Store};
 #[cfg(feature = "sled_store")]
@@ -32,6 +34,25 @@ impl SledStore {
         key
     }
 
+    fn decode_index(key: &[u8]) -> Result<u64, MerkleError> {
+        let bytes: [u8; 8] = key
+            .get(4..)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| MerkleError::StoreError("invalid key length".into()))?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    // Adds the metadata to the batch, removing the keys with an empty value.
+    fn write_metadata(batch: &mut Batch, metadata: &[(&str, &[u8])]) {
+        for (key, value) in metadata {
+            if value.is_empty() {
+                batch.remove(key.as_bytes());
+            } else {
+                batch.insert(key.as_bytes(), *value);
+            }
+        }
+    }
+
     fn decode_node(bytes: &[u8]) -> Result<Node, MerkleError> {
         // TODO: Options to allow zero copy? Eg using lifetimes on Node?
         let arr: [u8; Node::LEN] = bytes
@@ -43,8 +64,14 @@ impl SledStore {
 
 #[cfg(feature = "sled_store")]
 impl SledStore {
-    // TODO: Maybe return result
+    /// Opens the sled database at `file_path`, panicking on failure. See `try_new`.
     pub fn new(file_path: &str, temporary: bool) -> Self {
+        Self::try_new(file_path, temporary).expect("failed to open sled store")
+    }
+
+    /// Opens the sled database at `file_path`, or creates it if it doesn't exist. A
+    /// `temporary` database is removed when dropped.
+    pub fn try_new(file_path: &str, temporary: bool) -> Result<Self, MerkleError> {
         // Stuff that can be tunned, unused by now:
         // - mode (small vs fast)
         // - compression
@@ -53,24 +80,17 @@ impl SledStore {
             .path(file_path)
             .temporary(temporary)
             .open()
-            .expect("failed to open sled DB");
+            .map_err(|e| MerkleError::StoreOpenError(e.to_string()))?;
 
         // Load the persisted leaf count (big-endian u64) or default to 0.
         let num_leaves = db
             .get(Self::KEY_NUM_LEAVES)
-            .expect("failed to get num leaves")
-            .map(|ivec| {
-                let bytes: [u8; 8] = ivec.as_ref().try_into().expect("invalid num_leaves length");
-                u64::from_be_bytes(bytes)
-            })
+            .map_err(|e| MerkleError::StoreInitError(e.to_string()))?
+            .map(|ivec| decode_num_leaves(&ivec))
+            .transpose()?
             .unwrap_or(0);
 
-        // If the tree has no leaves, clear the db just in case.
-        if num_leaves == 0 {
-            db.clear().expect("failed to clear db");
-        }
-
-        Self { db, num_leaves }
+        Ok(Self { db, num_leaves })
     }
 }
 
@@ -100,26 +120,86 @@ impl Store for SledStore {
         result
     }
 
-    fn put(&mut self, items: &[(u32, u64, Node)]) -> Result<(), MerkleError> {
+    fn get_range(&self, level: u32, start: u64, end: u64) -> Result<Vec<(u64, Node)>, MerkleError> {
+        // Keys are sorted by level and then by index, so the range is a single scan.
+        let start_key = Self::encode_key(level, start);
+        let end_key = Self::encode_key(level, end);
+
+        self.db
+            .range(start_key..end_key)
+            .map(|item| {
+                let (key, value) = item.map_err(Self::db_error)?;
+                Ok((Self::decode_index(&key)?, Self::decode_node(&value)?))
+            })
+            .collect()
+    }
+
+    fn put_with_metadata(
+        &mut self,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
+        let mut batch = Batch::default();
+
+        for (level, index, node) in items.iter() {
+            let key = Self::encode_key(*level, *index);
+            batch.insert(&key, node.as_ref());
+        }
+
+        // Metadata keys are ascii strings so they never collide with the node keys,
+        // which always start with the big-endian level.
+        Self::write_metadata(&mut batch, metadata);
+
+        let new_leaves = next_num_leaves(self.num_leaves, items);
+        batch.insert(Self::KEY_NUM_LEAVES, &new_leaves.to_be_bytes());
+
+        self.db.apply_batch(batch).map_err(Self::db_error)?;
+        self.num_leaves = new_leaves;
+
+        Ok(())
+    }
+
+    fn truncate(
+        &mut self,
+        num_leaves: u64,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
         let mut batch = Batch::default();
 
+        // Sled doesn't support range deletes, so scan the keys to remove at each level.
+        for level in 0..=MAX_LEVEL {
+            let start = Self::encode_key(level, first_truncated_index(num_leaves, level));
+            let end = Self::encode_key(level, u64::MAX);
+            for key in self.db.range(start..=end).keys() {
+                batch.remove(key.map_err(Self::db_error)?);
+            }
+        }
+
         for (level, index, node) in items.iter() {
             let key = Self::encode_key(*level, *index);
             batch.insert(&key, node.as_ref());
         }
 
-        let counter = items.iter().filter(|(level, _, _)| *level == 0).count();
-        batch.insert(
-            Self::KEY_NUM_LEAVES,
-            &(self.num_leaves + counter as u64).to_be_bytes(),
-        );
+        Self::write_metadata(&mut batch, metadata);
+
+        let new_leaves = next_num_leaves(num_leaves, items);
+        batch.insert(Self::KEY_NUM_LEAVES, &new_leaves.to_be_bytes());
 
         self.db.apply_batch(batch).map_err(Self::db_error)?;
-        self.num_leaves += counter as u64;
+        self.num_leaves = new_leaves;
 
         Ok(())
     }
 
+    fn get_metadata(&self, key: &str) -> Result<Option<Vec<u8>>, MerkleError> {
+        Ok(self
+            .db
+            .get(key.as_bytes())
+            .map_err(Self::db_error)?
+            .map(|ivec| ivec.to_vec()))
+    }
+
     fn get_num_leaves(&self) -> u64 {
         self.num_leaves
     }
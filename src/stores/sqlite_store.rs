@@ -2,10 +2,14 @@
 
 //! SQLite store implementation.
 
+#[cfg(feature = "sqlite_store")]
+use crate::store::{decode_num_leaves, first_truncated_index, next_num_leaves, MAX_LEVEL};
 #[cfg(feature = "sqlite_store")]
 use crate::{MerkleError, Node, Store};
 #[cfg(feature = "sqlite_store")]
 use rusqlite::{params, Connection, OptionalExtension};
+#[cfg(feature = "sqlite_store")]
+use std::collections::HashMap;
 
 #[cfg(feature = "sqlite_store")]
 pub struct SqliteStore {
@@ -55,12 +59,79 @@ impl SqliteStore {
         (sql, binds)
     }
 
-    // Use ":memory:" for in-memory database.
+    // Indices are stored as the i64 with the same bits, so the ones from 2^63 on are
+    // negative and sort before the others. Splits the indices in `[lo, hi]` in at most two
+    // ranges of stored indices, sorted by index.
+    fn stored_ranges(lo: u64, hi: u64) -> Vec<(i64, i64)> {
+        const SIGN_BIT: u64 = 1 << 63;
+        let mut ranges = Vec::with_capacity(2);
+        if lo < SIGN_BIT {
+            ranges.push((lo as i64, hi.min(SIGN_BIT - 1) as i64));
+        }
+        if hi >= SIGN_BIT {
+            ranges.push((lo.max(SIGN_BIT) as i64, hi as i64));
+        }
+        ranges
+    }
+
+    // Writes the nodes and metadata within an ongoing transaction.
+    fn write_items(
+        tx: &rusqlite::Transaction,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
+        {
+            let mut insert_stmt = tx
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO nodes (level, idx, node) VALUES (?1, ?2, ?3)",
+                )
+                .map_err(Self::db_error)?;
+
+            for (level, index, node) in items {
+                insert_stmt
+                    .execute(params![*level as i64, *index as i64, node.as_ref()])
+                    .map_err(Self::db_error)?;
+            }
+        }
+
+        {
+            let mut metadata_stmt = tx
+                .prepare_cached("INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)")
+                .map_err(Self::db_error)?;
+            let mut delete_stmt = tx
+                .prepare_cached("DELETE FROM metadata WHERE key = ?1")
+                .map_err(Self::db_error)?;
+
+            // An empty value removes the key.
+            for (key, value) in metadata {
+                if value.is_empty() {
+                    delete_stmt.execute(params![key]).map_err(Self::db_error)?;
+                } else {
+                    metadata_stmt
+                        .execute(params![key, value])
+                        .map_err(Self::db_error)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Opens the SQLite database at `file_path`, panicking on failure. See `try_new`.
     pub fn new(file_path: &str) -> Self {
-        let conn = Connection::open(file_path).expect("failed to open sqlite DB");
+        Self::try_new(file_path).expect("failed to open sqlite store")
+    }
+
+    /// Opens the SQLite database at `file_path`, or creates it if it doesn't exist. Use
+    /// ":memory:" for an in-memory database.
+    pub fn try_new(file_path: &str) -> Result<Self, MerkleError> {
+        let init_error = |e: rusqlite::Error| MerkleError::StoreInitError(e.to_string());
+
+        let conn =
+            Connection::open(file_path).map_err(|e| MerkleError::StoreOpenError(e.to_string()))?;
 
         conn.execute_batch("PRAGMA journal_mode = WAL;\nPRAGMA synchronous = NORMAL;")
-            .expect("failed to set WAL mode and synchronous pragma");
+            .map_err(init_error)?;
 
         // Create schema if not exists.
         conn.execute_batch(
@@ -77,33 +148,22 @@ impl SqliteStore {
              );
              COMMIT;",
         )
-        .expect("failed to create tables");
+        .map_err(init_error)?;
 
         // Load persisted leaf count
-        let num_leaves: u64 = conn
+        let num_leaves = conn
             .query_row(
                 "SELECT value FROM metadata WHERE key = ?1",
                 params![Self::KEY_NUM_LEAVES],
-                |row| {
-                    let bytes: [u8; 8] = row
-                        .get::<_, Vec<u8>>(0)?
-                        .as_slice()
-                        .try_into()
-                        .map_err(|_| rusqlite::Error::InvalidQuery)?;
-                    Ok(u64::from_be_bytes(bytes))
-                },
+                |row| row.get::<_, Vec<u8>>(0),
             )
             .optional()
-            .expect("failed to query num leaves")
+            .map_err(init_error)?
+            .map(|bytes| decode_num_leaves(&bytes))
+            .transpose()?
             .unwrap_or(0);
 
-        // If the count is 0, clear the db, just in case.
-        if num_leaves == 0 {
-            conn.execute_batch("DELETE FROM nodes; DELETE FROM metadata;")
-                .expect("failed to clear inconsistent DB state");
-        }
-
-        Self { conn, num_leaves }
+        Ok(Self { conn, num_leaves })
     }
 }
 
@@ -121,13 +181,13 @@ impl Store for SqliteStore {
             return Ok(Vec::new());
         }
 
-        // Restrict to 256 elements to avoid SQLite parameter limit.
-        // Practically this should never happen.
+        // Split big reads in chunks of 256 elements to avoid SQLite parameter limit.
         if levels.len() > MAX_PARAMS {
-            return Err(MerkleError::StoreError(format!(
-                "levels length must be less than {}",
-                MAX_PARAMS
-            )));
+            let mut result = Vec::with_capacity(levels.len());
+            for (levels, indices) in levels.chunks(MAX_PARAMS).zip(indices.chunks(MAX_PARAMS)) {
+                result.extend(self.get(levels, indices)?);
+            }
+            return Ok(result);
         }
 
         let (values_sql, binds) = Self::build_values_sql_and_binds(levels, indices);
@@ -156,26 +216,46 @@ impl Store for SqliteStore {
         .collect::<Result<Vec<_>, _>>()
     }
 
-    fn put(&mut self, items: &[(u32, u64, Node)]) -> Result<(), MerkleError> {
-        let tx = self.conn.transaction().map_err(Self::db_error)?;
+    fn get_range(&self, level: u32, start: u64, end: u64) -> Result<Vec<(u64, Node)>, MerkleError> {
+        if start >= end {
+            return Ok(Vec::new());
+        }
 
-        {
-            let mut insert_stmt = tx
-                .prepare_cached(
-                    "INSERT OR REPLACE INTO nodes (level, idx, node) VALUES (?1, ?2, ?3)",
-                )
+        let mut stmt = self
+            .conn
+            .prepare_cached(
+                "SELECT idx, node FROM nodes WHERE level = ?1 AND idx >= ?2 AND idx <= ?3 \
+                 ORDER BY idx",
+            )
+            .map_err(Self::db_error)?;
+
+        let mut result = Vec::new();
+        for (lo, hi) in Self::stored_ranges(start, end - 1) {
+            let rows = stmt
+                .query_map(params![level as i64, lo, hi], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+                })
                 .map_err(Self::db_error)?;
 
-            for (level, index, node) in items {
-                insert_stmt
-                    .execute(params![*level as i64, *index as i64, node.as_ref()])
-                    .map_err(Self::db_error)?;
+            for row in rows {
+                let (idx, blob) = row.map_err(Self::db_error)?;
+                result.push((idx as u64, Self::decode_node(&blob)?));
             }
         }
+        Ok(result)
+    }
+
+    fn put_with_metadata(
+        &mut self,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
+        let tx = self.conn.transaction().map_err(Self::db_error)?;
+
+        Self::write_items(&tx, items, metadata)?;
 
-        let counter = items.iter().filter(|(level, _, _)| *level == 0).count() as u64;
-        if counter > 0 {
-            let new_leaves = self.num_leaves + counter;
+        let new_leaves = next_num_leaves(self.num_leaves, items);
+        if new_leaves != self.num_leaves {
             tx.execute(
                 "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
                 params![Self::KEY_NUM_LEAVES, new_leaves.to_be_bytes().to_vec()],
@@ -189,6 +269,78 @@ impl Store for SqliteStore {
         Ok(())
     }
 
+    fn truncate(
+        &mut self,
+        num_leaves: u64,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError> {
+        let tx = self.conn.transaction().map_err(Self::db_error)?;
+
+        {
+            let mut delete_stmt = tx
+                .prepare_cached("DELETE FROM nodes WHERE level = ?1 AND idx >= ?2 AND idx <= ?3")
+                .map_err(Self::db_error)?;
+
+            for level in 0..=MAX_LEVEL {
+                let first = first_truncated_index(num_leaves, level);
+                for (lo, hi) in Self::stored_ranges(first, u64::MAX) {
+                    delete_stmt
+                        .execute(params![level as i64, lo, hi])
+                        .map_err(Self::db_error)?;
+                }
+            }
+        }
+
+        Self::write_items(&tx, items, metadata)?;
+
+        let new_leaves = next_num_leaves(num_leaves, items);
+        tx.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+            params![Self::KEY_NUM_LEAVES, new_leaves.to_be_bytes().to_vec()],
+        )
+        .map_err(Self::db_error)?;
+
+        tx.commit().map_err(Self::db_error)?;
+        self.num_leaves = new_leaves;
+        Ok(())
+    }
+
+    fn get_metadata(&self, key: &str) -> Result<Option<Vec<u8>>, MerkleError> {
+        self.conn
+            .query_row(
+                "SELECT value FROM metadata WHERE key = ?1",
+                params![key],
+                |row| row.get::<_, Vec<u8>>(0),
+            )
+            .optional()
+            .map_err(Self::db_error)
+    }
+
+    fn get_metadata_many(&self, keys: &[&str]) -> Result<Vec<Option<Vec<u8>>>, MerkleError> {
+        let mut values: HashMap<String, Vec<u8>> = HashMap::with_capacity(keys.len());
+
+        for chunk in keys.chunks(MAX_PARAMS) {
+            let placeholders = vec!["?"; chunk.len()].join(",");
+            let sql = format!(
+                "SELECT key, value FROM metadata WHERE key IN ({})",
+                placeholders
+            );
+            let mut stmt = self.conn.prepare_cached(&sql).map_err(Self::db_error)?;
+            let rows = stmt
+                .query_map(rusqlite::params_from_iter(chunk), |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+                })
+                .map_err(Self::db_error)?;
+            for row in rows {
+                let (key, value) = row.map_err(Self::db_error)?;
+                values.insert(key, value);
+            }
+        }
+
+        Ok(keys.iter().map(|key| values.get(*key).cloned()).collect())
+    }
+
     fn get_num_leaves(&self) -> u64 {
         self.num_leaves
     }
@@ -204,4 +356,17 @@ mod tests {
         assert_eq!(sql, "(?, ?, ?),(?, ?, ?)");
         assert_eq!(binds, vec![33, 10, 0, 34, 20, 1]);
     }
+
+    #[test]
+    fn stored_ranges_split_at_sign_bit() {
+        assert_eq!(SqliteStore::stored_ranges(3, 10), vec![(3, 10)]);
+        assert_eq!(
+            SqliteStore::stored_ranges(3, u64::MAX),
+            vec![(3, i64::MAX), (i64::MIN, -1)]
+        );
+        assert_eq!(
+            SqliteStore::stored_ranges(1 << 63, (1 << 63) + 5),
+            vec![(i64::MIN, i64::MIN + 5)]
+        );
+    }
 }
@@ -16,10 +16,106 @@ pub trait Store {
     /// item means that the node is not present in the store.
     fn get(&self, levels: &[u32], indices: &[u64]) -> Result<Vec<Option<Node>>, MerkleError>;
 
+    /// Returns the nodes present at `level` with indices in the range `[start, end)`,
+    /// sorted by index. Nodes that are not present in the store are skipped. For example:
+    /// level=0, start=0, end=10 to fetch the first 10 leaves.
+    fn get_range(&self, level: u32, start: u64, end: u64) -> Result<Vec<(u64, Node)>, MerkleError>;
+
     /// Stores a list of nodes at the specified levels and indices. For example:
     /// items=[(0, 10, SomeNode)] will store SomeNode at level 0 and index 10.
-    fn put(&mut self, items: &[(u32, u64, Node)]) -> Result<(), MerkleError>;
+    /// Existing nodes are overwritten. Writing a leaf (level 0) beyond the current
+    /// number of leaves bumps it, while overwriting an existing leaf does not.
+    fn put(&mut self, items: &[(u32, u64, Node)]) -> Result<(), MerkleError> {
+        self.put_with_metadata(items, &[])
+    }
+
+    /// Same as `put` but also stores the given metadata key/value pairs in the same
+    /// batch. For example: metadata=[("SOME_KEY", &[1, 2, 3])]. An empty value removes
+    /// the key.
+    fn put_with_metadata(
+        &mut self,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError>;
+
+    /// Removes the nodes that fall beyond the first `num_leaves` leaves at every level and
+    /// sets the number of leaves to `num_leaves`. Then it stores `items` and `metadata` in
+    /// the same batch, so that nodes partially covered by the remaining leaves can be
+    /// rewritten. For example: num_leaves=5 removes the leaves from index 5 and the level 1
+    /// nodes from index 3. `num_leaves` can't be greater than the current number of leaves.
+    fn truncate(
+        &mut self,
+        num_leaves: u64,
+        items: &[(u32, u64, Node)],
+        metadata: &[(&str, &[u8])],
+    ) -> Result<(), MerkleError>;
 
-    /// Returns the number of leaves in the store.
+    /// Returns the metadata value stored under `key`, or None if it is not present.
+    /// Keys are ascii strings such as "NUM_LEAVES", which is reserved.
+    fn get_metadata(&self, key: &str) -> Result<Option<Vec<u8>>, MerkleError>;
+
+    /// Same as `get_metadata` for several keys at once. The result has the same length
+    /// as the keys. By default, the keys are read one by one.
+    fn get_metadata_many(&self, keys: &[&str]) -> Result<Vec<Option<Vec<u8>>>, MerkleError> {
+        keys.iter().map(|key| self.get_metadata(key)).collect()
+    }
+
+    /// Returns the number of leaves in the store, this is, one past the highest
+    /// leaf index written so far.
     fn get_num_leaves(&self) -> u64;
 }
+
+/// Highest level a node can be stored at. It's the root of a tree using the whole
+/// `u64` index space.
+#[cfg(any(
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
+pub(crate) const MAX_LEVEL: u32 = 64;
+
+/// Returns the index of the first node at `level` that falls beyond the first `num_leaves`
+/// leaves, this is, the number of nodes at that level covering at least one of them.
+#[cfg(any(
+    feature = "memory_store",
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
+pub(crate) fn first_truncated_index(num_leaves: u64, level: u32) -> u64 {
+    if level >= u64::BITS {
+        return u64::from(num_leaves != 0);
+    }
+    let mask = (1u64 << level) - 1;
+    (num_leaves >> level) + u64::from(num_leaves & mask != 0)
+}
+
+/// Returns the number of leaves after writing `items` to a store holding `num_leaves`
+/// leaves. Only leaves (level 0) written beyond the current count increase it.
+#[cfg(any(
+    feature = "memory_store",
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
+pub(crate) fn next_num_leaves(num_leaves: u64, items: &[(u32, u64, Node)]) -> u64 {
+    items
+        .iter()
+        .filter(|(level, _, _)| *level == 0)
+        .map(|(_, index, _)| index.saturating_add(1))
+        .fold(num_leaves, u64::max)
+}
+
+/// Decodes the persisted number of leaves, stored as a big-endian `u64`.
+#[cfg(any(
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
+pub(crate) fn decode_num_leaves(bytes: &[u8]) -> Result<u64, MerkleError> {
+    let bytes: [u8; 8] = bytes.try_into().map_err(|_| MerkleError::InvalidMetadata {
+        key: "NUM_LEAVES".into(),
+        len: bytes.len(),
+    })?;
+    Ok(u64::from_be_bytes(bytes))
+}
@@ -3,13 +3,48 @@
 //! Merkle tree implementation.
 
 use crate::hasher::{Hasher, Keccak256Hasher};
+use crate::proof::{ConsistencyProof, MultiProof, RangeProof};
 use crate::{MerkleError, Node, Store};
-use core::ops::Index;
+use core::cmp::Ordering;
+use core::ops::{Bound, Index, RangeBounds};
 use std::collections::HashMap;
 
 #[cfg(feature = "memory_store")]
 use crate::stores::MemoryStore;
 
+/// Metadata key holding the number of appended leaves that are empty.
+const KEY_NUM_EMPTY_LEAVES: &str = "NUM_EMPTY_LEAVES";
+
+/// Metadata key holding the most recent roots. See `MerkleTree::with_root_history`.
+const KEY_ROOT_HISTORY: &str = "ROOT_HISTORY";
+
+/// Number of leaves read from the store at once when iterating them.
+const LEAVES_PAGE_SIZE: u64 = 1024;
+
+/// Minimum number of nodes in a level to hash it in parallel. Below it, the overhead of
+/// splitting the work outweighs the gain.
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 1024;
+
+/// Returns the metadata key holding the newest index in the list of indices of `leaf`.
+/// See `MerkleTree::with_leaf_index`.
+fn leaf_index_key(leaf: &Node) -> String {
+    format!("LEAF_INDEX_{}", leaf)
+}
+
+/// Returns the metadata key linking `index` to the previous index in the list of indices
+/// of `leaf`, so that every index of a duplicated leaf can be reached.
+fn leaf_index_prev_key(leaf: &Node, index: u64) -> String {
+    format!("LEAF_INDEX_{}_{}", leaf, index)
+}
+
+fn decode_leaf_index(bytes: &[u8]) -> Result<u64, MerkleError> {
+    let bytes: [u8; 8] = bytes
+        .try_into()
+        .map_err(|_| MerkleError::StoreError("invalid leaf index length".into()))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
 pub struct MerkleProof<const DEPTH: usize> {
     pub proof: [Node; DEPTH],
     pub leaf: Node,
@@ -17,6 +52,27 @@ pub struct MerkleProof<const DEPTH: usize> {
     pub root: Node,
 }
 
+impl<const DEPTH: usize> MerkleProof<DEPTH> {
+    /// Verifies the proof without needing the tree.
+    pub fn verify<H: Hasher>(&self, hasher: &H) -> bool {
+        compute_root(hasher, &self.leaf, self.index, &self.proof) == self.root
+    }
+}
+
+/// Computes the root from a leaf at `index` and its siblings, from the leaves up.
+pub(crate) fn compute_root<H: Hasher>(hasher: &H, leaf: &Node, index: u64, proof: &[Node]) -> Node {
+    let mut computed_hash = *leaf;
+    for (j, sibling_hash) in proof.iter().enumerate() {
+        let (left, right) = if index & (1 << j) == 0 {
+            (computed_hash, *sibling_hash)
+        } else {
+            (*sibling_hash, computed_hash)
+        };
+        computed_hash = hasher.hash(&left, &right);
+    }
+    computed_hash
+}
+
 pub struct MerkleTree<H, S, const DEPTH: usize>
 where
     H: Hasher,
@@ -25,6 +81,8 @@ where
     hasher: H,
     store: S,
     zeros: Zeros<DEPTH>,
+    root_history_size: usize,
+    leaf_index: bool,
 }
 
 // Type alias for common configuration
@@ -44,6 +102,23 @@ pub struct Zeros<const DEPTH: usize> {
     last: Node,
 }
 
+impl<const DEPTH: usize> Zeros<DEPTH> {
+    /// Computes the root of an empty subtree at each level, from the empty leaf up to the
+    /// empty root at level `DEPTH`.
+    pub fn new<H: Hasher>(hasher: &H) -> Self {
+        const { assert!(DEPTH >= 1, "DEPTH must be at least 1") };
+
+        let mut zero = [Node::ZERO; DEPTH];
+        for i in 1..DEPTH {
+            zero[i] = hasher.hash(&zero[i - 1], &zero[i - 1]);
+        }
+        Zeros {
+            front: zero,
+            last: hasher.hash(&zero[DEPTH - 1], &zero[DEPTH - 1]),
+        }
+    }
+}
+
 // TODO: Maybe use "typenum" crate to avoid this.
 impl<const DEPTH: usize> Index<usize> for Zeros<DEPTH> {
     type Output = Node;
@@ -59,117 +134,781 @@ impl<const DEPTH: usize> Index<usize> for Zeros<DEPTH> {
     }
 }
 
-// TODO: Implement send and sync so that the tree can be used in a concurrent context
+/// Returns the maximum number of leaves of a tree of `depth` levels. A tree of depth 64 has
+/// 2^64 slots, but since the number of leaves is a `u64`, only `u64::MAX` can be appended.
+pub(crate) fn capacity(depth: usize) -> u64 {
+    if depth >= u64::BITS as usize {
+        u64::MAX
+    } else {
+        1 << depth
+    }
+}
+
+/// Returns true if `index` is a leaf position in a tree of `depth` levels.
+pub(crate) fn index_fits(depth: usize, index: u64) -> bool {
+    depth >= u64::BITS as usize || index >> depth == 0
+}
+
+/// Returns the levels and indices of the nodes to read from the store to append leaves to a
+/// tree holding `num_leaves` leaves. These are the left siblings along the path of the
+/// first new leaf, since the rest of the siblings are either computed in the same batch or
+/// empty.
+pub(crate) fn append_siblings(depth: usize, num_leaves: u64) -> (Vec<u32>, Vec<u64>) {
+    let mut levels: Vec<u32> = Vec::with_capacity(depth);
+    let mut indices: Vec<u64> = Vec::with_capacity(depth);
+    let mut idx = num_leaves;
+    for level in 0..depth {
+        if idx & 1 == 1 {
+            levels.push(level as u32);
+            indices.push(idx ^ 1);
+        }
+        idx >>= 1;
+    }
+    (levels, indices)
+}
+
+/// Computes the nodes to write to append `leaves` to a tree of `depth` levels holding
+/// `num_leaves` leaves. `siblings` are the nodes read at the positions returned by
+/// `append_siblings`, so no store is needed.
+pub(crate) fn hash_append<H, Z>(
+    hasher: &H,
+    zeros: &Z,
+    depth: usize,
+    num_leaves: u64,
+    leaves: &[Node],
+    siblings: &[Option<Node>],
+) -> Result<Vec<(u32, u64, Node)>, MerkleError>
+where
+    H: Hasher,
+    Z: Index<usize, Output = Node> + ?Sized,
+{
+    // Error if leaves do not fit in the tree
+    let capacity = capacity(depth);
+    if num_leaves
+        .checked_add(leaves.len() as u64)
+        .is_none_or(|num_leaves| num_leaves > capacity)
+    {
+        return Err(MerkleError::TreeFull {
+            depth: depth as u32,
+            capacity,
+        });
+    }
+
+    // Stores the levels and hashes to be written in a single batch. Each level holds about
+    // half the nodes of the one below, so the batch has about twice as many nodes as leaves.
+    let mut batch: Vec<(u32, u64, Node)> = Vec::with_capacity(2 * leaves.len() + depth);
+
+    // Hash one level at a time. `row` holds the new nodes of the current level, the first
+    // one at index `start`. Their left siblings are already hashed, either in `row` or, for
+    // the first node, in the store. Their right siblings are either in `row` or empty.
+    let mut siblings = siblings.iter();
+    let mut row: Vec<Node> = leaves.to_vec();
+    let mut parents: Vec<Node> = Vec::with_capacity(leaves.len() / 2 + 1);
+    let mut start = num_leaves;
+
+    for level in 0..depth {
+        batch.extend(
+            row.iter()
+                .enumerate()
+                .map(|(i, node)| (level as u32, start + i as u64, *node)),
+        );
+
+        parents.clear();
+        let mut rest = &row[..];
+        if start & 1 == 1 {
+            let left = siblings.next().copied().flatten().unwrap_or(zeros[level]);
+            parents.push(hasher.hash(&left, &row[0]));
+            rest = &row[1..];
+        }
+        hash_pairs(hasher, rest, &zeros[level], &mut parents);
+
+        std::mem::swap(&mut row, &mut parents);
+        start >>= 1;
+    }
+
+    // The last node is the root.
+    batch.push((depth as u32, 0, row[0]));
+
+    Ok(batch)
+}
+
+/// Hashes each pair of consecutive nodes in `row` into `parents`, pairing the last one with
+/// `zero` if the length is odd. With the `parallel` feature, rows of at least
+/// `PARALLEL_THRESHOLD` nodes are hashed on the rayon thread pool, keeping the order.
+fn hash_pairs<H: Hasher>(hasher: &H, row: &[Node], zero: &Node, parents: &mut Vec<Node>) {
+    let hash_pair = |pair: &[Node]| match pair {
+        [left, right] => hasher.hash(left, right),
+        [left] => hasher.hash(left, zero),
+        _ => unreachable!("chunks of two"),
+    };
+
+    #[cfg(feature = "parallel")]
+    if row.len() >= PARALLEL_THRESHOLD {
+        use rayon::prelude::*;
+        parents.par_extend(row.par_chunks(2).map(hash_pair));
+        return;
+    }
+
+    parents.extend(row.chunks(2).map(hash_pair));
+}
+
+/// Computes the nodes to write to append `leaves` to a tree of `depth` levels, reading the
+/// siblings that are not computed in this batch from the store. Shared by `MerkleTree`
+/// and `DynMerkleTree`.
+pub(crate) fn append_batch<H, S, Z>(
+    hasher: &H,
+    store: &S,
+    zeros: &Z,
+    depth: usize,
+    leaves: &[Node],
+) -> Result<Vec<(u32, u64, Node)>, MerkleError>
+where
+    H: Hasher,
+    S: Store,
+    Z: Index<usize, Output = Node> + ?Sized,
+{
+    let num_leaves = store.get_num_leaves();
+    let (levels, indices) = append_siblings(depth, num_leaves);
+    let siblings = store.get(&levels, &indices)?;
+    hash_append(hasher, zeros, depth, num_leaves, leaves, &siblings)
+}
+
+/// Returns the levels and indices of the siblings of `leaf_idx` at each level of a tree of
+/// `depth` levels, followed by the leaf itself and the root.
+pub(crate) fn path_keys(depth: usize, leaf_idx: u64) -> Result<(Vec<u32>, Vec<u64>), MerkleError> {
+    if !index_fits(depth, leaf_idx) {
+        return Err(MerkleError::LeafIndexOutOfBounds {
+            index: leaf_idx,
+            num_leaves: capacity(depth),
+        });
+    }
+
+    // Build level/index lists for siblings plus the leaf and the root.
+    let mut levels: Vec<u32> = Vec::with_capacity(depth + 2);
+    let mut indices: Vec<u64> = Vec::with_capacity(depth + 2);
+
+    let mut idx = leaf_idx;
+    for level in 0..depth {
+        let sibling = idx ^ 1;
+        levels.push(level as u32);
+        indices.push(sibling);
+        idx >>= 1;
+    }
+
+    // Append the leaf itself at index leaf_idx, and the root.
+    levels.extend([0, depth as u32]);
+    indices.extend([leaf_idx, 0]);
+
+    Ok((levels, indices))
+}
+
+/// Splits the nodes read at the positions returned by `path_keys` into the siblings, the
+/// leaf and the root, replacing the missing ones with empty subtrees.
+pub(crate) fn path_from_fetched<Z>(
+    zeros: &Z,
+    depth: usize,
+    fetched: &[Option<Node>],
+) -> (Vec<Node>, Node, Node)
+where
+    Z: Index<usize, Output = Node> + ?Sized,
+{
+    // The first depth items are the siblings.
+    let proof = fetched
+        .iter()
+        .take(depth)
+        .enumerate()
+        .map(|(d, opt)| opt.unwrap_or(zeros[d]))
+        .collect();
+
+    // The last items are the leaf itself and the root.
+    let leaf_hash = fetched[depth].unwrap_or(zeros[0]);
+    let root = fetched[depth + 1].unwrap_or(zeros[depth]);
+
+    (proof, leaf_hash, root)
+}
+
+/// Reads the siblings of `leaf_idx` at each level of a tree of `depth` levels, plus the
+/// leaf itself and the root, in a single batch. Shared by `MerkleTree` and
+/// `DynMerkleTree`.
+pub(crate) fn read_path<S, Z>(
+    store: &S,
+    zeros: &Z,
+    depth: usize,
+    leaf_idx: u64,
+) -> Result<(Vec<Node>, Node, Node), MerkleError>
+where
+    S: Store,
+    Z: Index<usize, Output = Node> + ?Sized,
+{
+    let (levels, indices) = path_keys(depth, leaf_idx)?;
+
+    // Batch fetch all requested nodes.
+    let fetched = store.get(&levels, &indices)?;
+
+    Ok(path_from_fetched(zeros, depth, &fetched))
+}
 
 impl<H, S, const DEPTH: usize> MerkleTree<H, S, DEPTH>
 where
     H: Hasher,
     S: Store,
 {
+    /// Creates a tree on top of `store`, panicking if it can't be used. See `try_new`.
     pub fn new(hasher: H, store: S) -> Self {
-        // TODO: Protect from overflow. Eg if depth is 256, then it will overflow.
-        // Set a limit, maybe no more than 64?
-        let mut zero = [Node::ZERO; DEPTH];
-        for i in 1..DEPTH {
-            zero[i] = hasher.hash(&zero[i - 1], &zero[i - 1]);
+        Self::try_new(hasher, store).expect("failed to create merkle tree")
+    }
+
+    /// Creates a tree on top of `store`, which may already hold leaves. Errors if the store
+    /// can't be read or holds more leaves than fit in a tree of `DEPTH` levels.
+    pub fn try_new(hasher: H, store: S) -> Result<Self, MerkleError> {
+        // Indices are u64, so deeper trees can't be addressed.
+        const { assert!(DEPTH >= 1 && DEPTH <= 64, "DEPTH must be between 1 and 64") };
+
+        let num_leaves = store.get_num_leaves();
+        if num_leaves > capacity(DEPTH) {
+            return Err(MerkleError::CapacityExceeded {
+                num_leaves,
+                capacity: capacity(DEPTH),
+            });
         }
-        let zeros = Zeros {
-            front: zero,
-            last: hasher.hash(&zero[DEPTH - 1], &zero[DEPTH - 1]),
-        };
-        Self {
+
+        let zeros = Zeros::new(&hasher);
+        let tree = Self {
             hasher,
             store,
             zeros,
-        }
+            root_history_size: 0,
+            leaf_index: false,
+        };
+
+        // Surface store errors now rather than on first use.
+        tree.root()?;
+        Ok(tree)
+    }
+
+    /// Keeps a reverse index from each non-empty leaf to its index, so that leaves can be
+    /// found by value with `index_of`. The index is persisted in the store and only covers
+    /// the leaves written after enabling it. Disabled by default.
+    pub fn with_leaf_index(mut self) -> Self {
+        self.leaf_index = true;
+        self
+    }
+
+    /// Keeps the roots after the last `size` calls to `add_leaves`, so that proofs against
+    /// any of them are accepted by `is_known_root`. The history is persisted in the store.
+    /// Disabled by default.
+    pub fn with_root_history(mut self, size: usize) -> Self {
+        self.root_history_size = size;
+        self
     }
 
     pub fn add_leaves(&mut self, leaves: &[Node]) -> Result<(), MerkleError> {
+        self.add_leaves_with_metadata(leaves, Vec::new())
+    }
+
+    /// Same as `add_leaves`, also writing `metadata` in the same batch.
+    pub(crate) fn add_leaves_with_metadata(
+        &mut self,
+        leaves: &[Node],
+        mut metadata: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), MerkleError> {
         // Early return
         if leaves.is_empty() {
             return Ok(());
         }
 
-        // Error if leaves do not fit in the tree
-        // TODO: Avoid calculating this. Calculate it at init or do the shifting with the generic.
-        if self.store.get_num_leaves() + leaves.len() as u64 > (1 << DEPTH as u64) {
-            return Err(MerkleError::TreeFull {
-                depth: DEPTH as u32,
-                capacity: 1 << DEPTH as u64,
-            });
+        let batch = append_batch(&self.hasher, &self.store, &self.zeros, DEPTH, leaves)?;
+
+        // Metadata updated along with the nodes.
+
+        let appended_empty = leaves.iter().filter(|leaf| **leaf == self.zeros[0]).count() as u64;
+        if appended_empty != 0 {
+            let num_empty = self.num_empty_leaves()? + appended_empty;
+            metadata.push((
+                KEY_NUM_EMPTY_LEAVES.to_string(),
+                num_empty.to_be_bytes().to_vec(),
+            ));
+        }
+
+        if self.root_history_size != 0 {
+            // The last node in the batch is the new root.
+            let (_, _, root) = batch[batch.len() - 1];
+            let mut history = self.root_history()?;
+            history.push((self.store.get_num_leaves() + leaves.len() as u64, root));
+            let oldest = history.len().saturating_sub(self.root_history_size);
+            metadata.push((
+                KEY_ROOT_HISTORY.to_string(),
+                Self::encode_root_history(&history[oldest..]),
+            ));
+        }
+
+        if self.leaf_index {
+            let first_idx = self.store.get_num_leaves();
+            let written: Vec<(u64, Node)> = (first_idx..).zip(leaves.iter().copied()).collect();
+            metadata.extend(self.leaf_index_metadata(&written)?);
         }
 
-        // Stores the levels and hashes to be written in a single batch.
-        // This allows to batch all writes in a single batch transaction.
-        let mut batch: Vec<(u32, u64, Node)> = Vec::with_capacity(leaves.len() * (DEPTH + 1));
+        // Update all values in a single batch
+        self.put_batch(&batch, &metadata)
+    }
+
+    /// Writes the nodes and metadata in a single batch.
+    pub(crate) fn put_batch(
+        &mut self,
+        batch: &[(u32, u64, Node)],
+        metadata: &[(String, Vec<u8>)],
+    ) -> Result<(), MerkleError> {
+        let metadata: Vec<(&str, &[u8])> = metadata
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_slice()))
+            .collect();
+        self.store.put_with_metadata(batch, &metadata)
+    }
+
+    /// Replaces the leaf at `index` with `leaf` and recomputes the path up to the root.
+    /// All changed nodes are written in a single batch. Only leaves that were already
+    /// appended can be updated.
+    pub fn update_leaf(&mut self, index: u64, leaf: Node) -> Result<(), MerkleError> {
+        self.update_leaves(&[(index, leaf)])
+    }
 
-        // Cache for nodes generated in this batch so we can reuse them
-        let mut cache: HashMap<(u32, u64), Node> = HashMap::new();
+    /// Replaces multiple leaves given as `(index, leaf)` pairs and recomputes the affected
+    /// nodes. Ancestors shared by several leaves are hashed only once, and all changed nodes
+    /// are written in a single batch. If an index is repeated, the last value wins.
+    pub fn update_leaves(&mut self, leaves: &[(u64, Node)]) -> Result<(), MerkleError> {
+        self.update_leaves_with_metadata(leaves, Vec::new())
+    }
 
-        for (offset, leaf) in leaves.iter().enumerate() {
-            let mut idx = self.store.get_num_leaves() + offset as u64;
-            let mut h = *leaf;
+    /// Same as `update_leaves`, also writing `metadata` in the same batch.
+    pub(crate) fn update_leaves_with_metadata(
+        &mut self,
+        leaves: &[(u64, Node)],
+        mut metadata: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), MerkleError> {
+        // Early return
+        if leaves.is_empty() {
+            return Ok(());
+        }
 
-            // Store the leaf
-            batch.push((0, idx, h));
-            cache.insert((0, idx), h);
+        let num_leaves = self.store.get_num_leaves();
+        if let Some(&(index, _)) = leaves.iter().find(|(index, _)| *index >= num_leaves) {
+            return Err(MerkleError::LeafIndexOutOfBounds { index, num_leaves });
+        }
 
-            // Collect siblings that are not already cached so we can fetch them in one batch.
-            let mut levels_to_fetch = [0u32; DEPTH];
-            let mut indices_to_fetch = [0u64; DEPTH];
-            let mut fetch_len = 0usize;
+        // Sorted indices of the nodes that change at each level.
+        let mut dirty: Vec<Vec<u64>> = Vec::with_capacity(DEPTH + 1);
+        let mut indices: Vec<u64> = leaves.iter().map(|(index, _)| *index).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        dirty.push(indices);
+        for level in 0..DEPTH {
+            let mut parents: Vec<u64> = dirty[level].iter().map(|idx| idx >> 1).collect();
+            parents.dedup();
+            dirty.push(parents);
+        }
 
-            let mut tmp_idx = idx;
-            for lvl in 0..DEPTH {
-                let sibling_idx = tmp_idx ^ 1;
-                if !cache.contains_key(&(lvl as u32, sibling_idx)) {
-                    levels_to_fetch[fetch_len] = lvl as u32;
-                    indices_to_fetch[fetch_len] = sibling_idx;
-                    fetch_len += 1;
+        // The current leaves, needed to track the empty ones, and the siblings that
+        // don't change in this batch are fetched in a single read.
+        let mut levels_to_fetch: Vec<u32> = vec![0; dirty[0].len()];
+        let mut indices_to_fetch: Vec<u64> = dirty[0].clone();
+        for (level, indices) in dirty.iter().take(DEPTH).enumerate() {
+            for idx in indices {
+                let sibling_idx = idx ^ 1;
+                if indices.binary_search(&sibling_idx).is_err() {
+                    levels_to_fetch.push(level as u32);
+                    indices_to_fetch.push(sibling_idx);
                 }
-                tmp_idx >>= 1;
             }
+        }
+        let mut fetched = self.store.get(&levels_to_fetch, &indices_to_fetch)?;
+        let old_leaves: Vec<Option<Node>> = fetched.drain(..dirty[0].len()).collect();
 
-            // Batch-fetch the missing siblings and insert them in cache.
-            if fetch_len != 0 {
-                let fetched = self.store.get(
-                    &levels_to_fetch[..fetch_len],
-                    &indices_to_fetch[..fetch_len],
-                )?;
-
-                for (i, maybe_node) in fetched.into_iter().enumerate() {
-                    if let Some(node) = maybe_node {
-                        cache.insert((levels_to_fetch[i], indices_to_fetch[i]), node);
-                    }
-                }
+        // Cache with the fetched siblings and the nodes generated in this batch.
+        let mut cache: HashMap<(u32, u64), Node> = HashMap::with_capacity(fetched.len());
+        for ((&level, &idx), maybe_node) in levels_to_fetch
+            .iter()
+            .zip(&indices_to_fetch)
+            .skip(dirty[0].len())
+            .zip(fetched)
+        {
+            cache.insert(
+                (level, idx),
+                maybe_node.unwrap_or(self.zeros[level as usize]),
+            );
+        }
+        for &(index, leaf) in leaves {
+            cache.insert((0, index), leaf);
+        }
+
+        let mut batch: Vec<(u32, u64, Node)> = Vec::with_capacity(dirty.iter().map(Vec::len).sum());
+        for &idx in &dirty[0] {
+            batch.push((0, idx, cache[&(0, idx)]));
+        }
+
+        for level in 0..DEPTH {
+            for &idx in &dirty[level + 1] {
+                let left = cache[&(level as u32, idx << 1)];
+                let right = cache[&(level as u32, (idx << 1) | 1)];
+                let h = self.hasher.hash(&left, &right);
+
+                batch.push(((level + 1) as u32, idx, h));
+                cache.insert(((level + 1) as u32, idx), h);
             }
+        }
+
+        // Keep track of the slots that were emptied or filled.
+        let was_empty = old_leaves
+            .iter()
+            .filter(|old| old.unwrap_or(self.zeros[0]) == self.zeros[0])
+            .count() as u64;
+        let is_empty = dirty[0]
+            .iter()
+            .filter(|idx| cache[&(0, **idx)] == self.zeros[0])
+            .count() as u64;
+
+        // Metadata updated along with the nodes.
+
+        if was_empty != is_empty {
+            let num_empty = (self.num_empty_leaves()? + is_empty).saturating_sub(was_empty);
+            metadata.push((
+                KEY_NUM_EMPTY_LEAVES.to_string(),
+                num_empty.to_be_bytes().to_vec(),
+            ));
+        }
 
+        // Old values are not removed from the leaf index, since lookups check that the
+        // leaf is still there.
+        if self.leaf_index {
+            let written: Vec<(u64, Node)> = dirty[0]
+                .iter()
+                .map(|idx| (*idx, cache[&(0, *idx)]))
+                .collect();
+            metadata.extend(self.leaf_index_metadata(&written)?);
+        }
+
+        // Update all values in a single batch
+        self.put_batch(&batch, &metadata)
+    }
+
+    /// Removes the leaf at `index` by setting it back to the empty leaf and recomputing
+    /// its ancestors. The tree ends up exactly as if the slot was never filled, so
+    /// subtrees that become empty again hash to their empty-subtree value. The number of
+    /// leaves is not modified, since it is the position where the next leaf is appended.
+    /// See `num_live_leaves` for the number of non-empty leaves.
+    pub fn remove_leaf(&mut self, index: u64) -> Result<(), MerkleError> {
+        self.update_leaves(&[(index, self.zeros[0])])
+    }
+
+    /// Rolls the tree back to its first `new_len` leaves, as if the ones after them were
+    /// never appended. Nodes beyond the remaining leaves are removed from the store and the
+    /// right edge of every level is recomputed, all in a single batch.
+    pub fn truncate(&mut self, new_len: u64) -> Result<(), MerkleError> {
+        let num_leaves = self.store.get_num_leaves();
+        if new_len > num_leaves {
+            return Err(MerkleError::LeafIndexOutOfBounds {
+                index: new_len,
+                num_leaves,
+            });
+        }
+
+        // Early return
+        if new_len == num_leaves {
+            return Ok(());
+        }
+
+        // Fetch the dropped leaves to track the empty ones, plus the last remaining leaf and
+        // its left siblings to recompute the right edge. Right siblings are now empty.
+        let mut levels: Vec<u32> = vec![0; (num_leaves - new_len) as usize];
+        let mut indices: Vec<u64> = (new_len..num_leaves).collect();
+        if new_len > 0 {
+            let mut idx = new_len - 1;
+            levels.push(0);
+            indices.push(idx);
             for level in 0..DEPTH {
-                let sibling_idx = idx ^ 1;
+                if idx & 1 == 1 {
+                    levels.push(level as u32);
+                    indices.push(idx ^ 1);
+                }
+                idx >>= 1;
+            }
+        }
+        let mut fetched = self.store.get(&levels, &indices)?.into_iter();
 
-                let sib_hash = cache
-                    .get(&(level as u32, sibling_idx))
-                    .copied()
-                    .unwrap_or(self.zeros[level]);
+        let dropped_empty = fetched
+            .by_ref()
+            .take((num_leaves - new_len) as usize)
+            .filter(|leaf| leaf.unwrap_or(self.zeros[0]) == self.zeros[0])
+            .count() as u64;
 
-                let (left, right) = if idx & 1 == 1 {
-                    (sib_hash, h)
+        let mut batch: Vec<(u32, u64, Node)> = Vec::with_capacity(DEPTH);
+        if new_len > 0 {
+            let mut idx = new_len - 1;
+            let mut h = fetched.next().flatten().unwrap_or(self.zeros[0]);
+
+            for level in 0..DEPTH {
+                h = if idx & 1 == 1 {
+                    let sib_hash = fetched.next().flatten().unwrap_or(self.zeros[level]);
+                    self.hasher.hash(&sib_hash, &h)
                 } else {
-                    (h, sib_hash)
+                    self.hasher.hash(&h, &self.zeros[level])
                 };
-
-                h = self.hasher.hash(&left, &right);
                 idx >>= 1;
 
                 batch.push(((level + 1) as u32, idx, h));
-                cache.insert(((level + 1) as u32, idx), h);
             }
         }
 
-        // Update all values in a single batch
-        self.store.put(&batch)?;
+        let num_empty = self.num_empty_leaves()?.saturating_sub(dropped_empty);
+
+        // Roots recorded after the remaining leaves are no longer valid. The others are
+        // kept, even if the history is disabled in this tree.
+        let mut history = self.stored_root_history()?;
+        history.retain(|(num_leaves, _)| *num_leaves <= new_len);
+        let history = Self::encode_root_history(&history);
+
+        self.store.truncate(
+            new_len,
+            &batch,
+            &[
+                (KEY_NUM_EMPTY_LEAVES, &num_empty.to_be_bytes()),
+                (KEY_ROOT_HISTORY, &history),
+            ],
+        )
+    }
+
+    /// Returns the leaf at `index`. Only leaves that were already appended can be read.
+    pub fn get_leaf(&self, index: u64) -> Result<Node, MerkleError> {
+        let num_leaves = self.store.get_num_leaves();
+        if index >= num_leaves {
+            return Err(MerkleError::LeafIndexOutOfBounds { index, num_leaves });
+        }
+
+        Ok(self
+            .store
+            .get(&[0], &[index])?
+            .into_iter()
+            .next()
+            .flatten()
+            .unwrap_or(self.zeros[0]))
+    }
+
+    /// Returns an iterator over the appended leaves in `range`, in order. Leaves are read
+    /// from the store in pages, so it can be used to go through big trees. The range is
+    /// clamped to the number of leaves, eg `leaves(100..)` returns from index 100 to the end.
+    pub fn leaves(
+        &self,
+        range: impl RangeBounds<u64>,
+    ) -> impl Iterator<Item = Result<Node, MerkleError>> + '_ {
+        let num_leaves = self.store.get_num_leaves();
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.saturating_add(1),
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => num_leaves,
+        }
+        .min(num_leaves);
+
+        (start..end)
+            .step_by(LEAVES_PAGE_SIZE as usize)
+            .flat_map(move |page_start| {
+                let page_end = end.min(page_start.saturating_add(LEAVES_PAGE_SIZE));
+                let page: Vec<Result<Node, MerkleError>> =
+                    match self.store.get_range(0, page_start, page_end) {
+                        // Leaves missing in the store are empty.
+                        Ok(nodes) => {
+                            let mut nodes = nodes.into_iter().peekable();
+                            (page_start..page_end)
+                                .map(|idx| {
+                                    Ok(nodes
+                                        .next_if(|(node_idx, _)| *node_idx == idx)
+                                        .map_or(self.zeros[0], |(_, node)| node))
+                                })
+                                .collect()
+                        }
+                        Err(e) => vec![Err(e)],
+                    };
+                page
+            })
+    }
+
+    /// Returns the leaf index entries for the leaves written at the given indices. The
+    /// indices of each leaf form a list starting at `leaf_index_key`, with each index
+    /// linking to the previous one and the oldest to itself. An index joins the list the
+    /// first time the leaf is written at it and is never removed.
+    fn leaf_index_metadata(
+        &self,
+        written: &[(u64, Node)],
+    ) -> Result<Vec<(String, Vec<u8>)>, MerkleError> {
+        let written: Vec<(u64, Node)> = written
+            .iter()
+            .copied()
+            .filter(|(_, leaf)| *leaf != self.zeros[0])
+            .collect();
+        let head_keys: Vec<String> = written
+            .iter()
+            .map(|(_, leaf)| leaf_index_key(leaf))
+            .collect();
+        let link_keys: Vec<String> = written
+            .iter()
+            .map(|(idx, leaf)| leaf_index_prev_key(leaf, *idx))
+            .collect();
+        let keys: Vec<&str> = head_keys
+            .iter()
+            .chain(&link_keys)
+            .map(String::as_str)
+            .collect();
+        let fetched = self.store.get_metadata_many(&keys)?;
+        let (heads_fetched, links_fetched) = fetched.split_at(written.len());
+
+        let mut heads: HashMap<&str, Option<u64>> = HashMap::new();
+        for (key, head) in head_keys.iter().zip(heads_fetched) {
+            let head = head.as_deref().map(decode_leaf_index).transpose()?;
+            heads.entry(key.as_str()).or_insert(head);
+        }
+
+        let mut metadata = Vec::new();
+        for ((idx, head_key), (link_key, link)) in written
+            .iter()
+            .map(|(idx, _)| *idx)
+            .zip(&head_keys)
+            .zip(link_keys.iter().zip(links_fetched))
+        {
+            if link.is_some() {
+                continue;
+            }
+            let head = heads.insert(head_key.as_str(), Some(idx)).flatten();
+            let prev = head.unwrap_or(idx);
+            metadata.push((link_key.clone(), prev.to_be_bytes().to_vec()));
+        }
+        metadata.extend(heads.into_iter().filter_map(|(key, head)| {
+            head.map(|head| (key.to_string(), head.to_be_bytes().to_vec()))
+        }));
+
+        Ok(metadata)
+    }
+
+    /// Returns the index of `leaf`, or None if it's not in the tree. If the leaf is at
+    /// several indices, the one it was first written at most recently is returned.
+    /// Requires the leaf index to be enabled with `with_leaf_index` before appending the
+    /// leaf.
+    ///
+    /// Lookups walk back through every index the leaf was ever written at, so leaves
+    /// written at many indices that no longer hold them get slower to find.
+    pub fn index_of(&self, leaf: &Node) -> Result<Option<u64>, MerkleError> {
+        let Some(bytes) = self.store.get_metadata(&leaf_index_key(leaf))? else {
+            return Ok(None);
+        };
+        let mut index = decode_leaf_index(&bytes)?;
+
+        loop {
+            // The leaf may have been updated, removed or truncated since it was indexed.
+            if index < self.store.get_num_leaves() {
+                let current = self.store.get(&[0], &[index])?.into_iter().next().flatten();
+                if current == Some(*leaf) {
+                    return Ok(Some(index));
+                }
+            }
+
+            let Some(bytes) = self.store.get_metadata(&leaf_index_prev_key(leaf, index))? else {
+                return Ok(None);
+            };
+            let prev = decode_leaf_index(&bytes)?;
+            if prev == index {
+                return Ok(None);
+            }
+            index = prev;
+        }
+    }
+
+    /// Returns the proof of `leaf` without knowing its index, or None if it's not in the
+    /// tree. See `index_of`.
+    pub fn proof_for_leaf(&self, leaf: &Node) -> Result<Option<MerkleProof<DEPTH>>, MerkleError> {
+        self.index_of(leaf)?
+            .map(|index| self.proof(index))
+            .transpose()
+    }
 
-        Ok(())
+    /// Returns the number of appended leaves that are not empty. Leaves set to the empty
+    /// leaf, either removed or appended as such, are not counted.
+    pub fn num_live_leaves(&self) -> Result<u64, MerkleError> {
+        Ok(self.store.get_num_leaves() - self.num_empty_leaves()?)
+    }
+
+    fn num_empty_leaves(&self) -> Result<u64, MerkleError> {
+        self.store
+            .get_metadata(KEY_NUM_EMPTY_LEAVES)?
+            .map(|bytes| {
+                let bytes: [u8; 8] = bytes.as_slice().try_into().map_err(|_| {
+                    MerkleError::StoreError("invalid num_empty_leaves length".into())
+                })?;
+                Ok(u64::from_be_bytes(bytes))
+            })
+            .unwrap_or(Ok(0))
+    }
+
+    /// Returns the roots recorded after the last `add_leaves` calls, from the oldest to
+    /// the newest. See `with_root_history`.
+    pub fn recent_roots(&self) -> Result<Vec<Node>, MerkleError> {
+        Ok(self
+            .root_history()?
+            .into_iter()
+            .map(|(_, root)| root)
+            .collect())
+    }
+
+    /// Returns true if `root` is the current root or one of the recent roots. This allows
+    /// accepting proofs generated against a slightly outdated tree.
+    pub fn is_known_root(&self, root: &Node) -> Result<bool, MerkleError> {
+        Ok(self.root()? == *root || self.recent_roots()?.contains(root))
+    }
+
+    /// Returns the recorded roots along with the number of leaves they were recorded at,
+    /// keeping only the most recent ones if the history size was reduced.
+    fn root_history(&self) -> Result<Vec<(u64, Node)>, MerkleError> {
+        let mut history = self.stored_root_history()?;
+        history.drain(..history.len().saturating_sub(self.root_history_size));
+        Ok(history)
+    }
+
+    /// Returns all the roots in the store, regardless of the history size.
+    fn stored_root_history(&self) -> Result<Vec<(u64, Node)>, MerkleError> {
+        let Some(bytes) = self.store.get_metadata(KEY_ROOT_HISTORY)? else {
+            return Ok(Vec::new());
+        };
+
+        const ENTRY_LEN: usize = 8 + Node::LEN;
+        if bytes.len() % ENTRY_LEN != 0 {
+            return Err(MerkleError::StoreError(
+                "invalid root history length".into(),
+            ));
+        }
+
+        Ok(bytes
+            .chunks_exact(ENTRY_LEN)
+            .map(|entry| {
+                let (num_leaves, root) = entry.split_at(8);
+                let num_leaves: [u8; 8] = num_leaves.try_into().expect("entry is 40 bytes");
+                let root: [u8; Node::LEN] = root.try_into().expect("entry is 40 bytes");
+                (u64::from_be_bytes(num_leaves), Node::from(root))
+            })
+            .collect())
+    }
+
+    /// Encodes `history` as big-endian number of leaves followed by the root.
+    fn encode_root_history(history: &[(u64, Node)]) -> Vec<u8> {
+        history
+            .iter()
+            .flat_map(|(num_leaves, root)| {
+                num_leaves
+                    .to_be_bytes()
+                    .into_iter()
+                    .chain(root.as_ref().iter().copied())
+            })
+            .collect()
     }
 
     pub fn root(&self) -> Result<Node, MerkleError> {
@@ -182,6 +921,53 @@ where
             .unwrap_or(self.zeros[DEPTH]))
     }
 
+    /// Returns the root as it was right after the first `num_leaves` leaves were appended.
+    /// Since the tree is append-only, nodes fully on the left of that size did not change
+    /// afterwards, and the ones on the right were empty. Note that leaves modified with
+    /// `update_leaf` or `remove_leaf` are seen with their current value.
+    pub fn root_at(&self, num_leaves: u64) -> Result<Node, MerkleError> {
+        let current_leaves = self.store.get_num_leaves();
+        if num_leaves > current_leaves {
+            return Err(MerkleError::LeafIndexOutOfBounds {
+                index: num_leaves,
+                num_leaves: current_leaves,
+            });
+        }
+
+        if num_leaves == current_leaves {
+            return self.root();
+        }
+
+        Ok(self.right_edge_at(num_leaves)?[DEPTH])
+    }
+
+    /// Returns the nodes along the right edge of the tree as it was with `num_leaves`
+    /// leaves, from the leaves up to the root. The node at level `l` is the one at index
+    /// `num_leaves >> l`, only taking into account the leaves below `num_leaves`. All its
+    /// left siblings are complete, so they are fetched in a single read.
+    fn right_edge_at(&self, num_leaves: u64) -> Result<Vec<Node>, MerkleError> {
+        let (levels, indices) = append_siblings(DEPTH, num_leaves);
+        let mut fetched = self.store.get(&levels, &indices)?.into_iter();
+
+        let mut edge: Vec<Node> = Vec::with_capacity(DEPTH + 1);
+        let mut idx = num_leaves;
+        let mut h = self.zeros[0];
+        edge.push(h);
+
+        for level in 0..DEPTH {
+            h = if idx & 1 == 1 {
+                let sib_hash = fetched.next().flatten().unwrap_or(self.zeros[level]);
+                self.hasher.hash(&sib_hash, &h)
+            } else {
+                self.hasher.hash(&h, &self.zeros[level])
+            };
+            idx >>= 1;
+            edge.push(h);
+        }
+
+        Ok(edge)
+    }
+
     pub fn proof(&self, leaf_idx: u64) -> Result<MerkleProof<DEPTH>, MerkleError> {
         // Implementation detail. Allow proofs even beyond the number of leaves.
         // Since it has fixed depth it is technically correct.
@@ -194,64 +980,250 @@ where
         //    .into());
         //}
 
-        if leaf_idx > 1 << DEPTH as u64 {
+        let (siblings, leaf_hash, root) = read_path(&self.store, &self.zeros, DEPTH, leaf_idx)?;
+
+        let mut proof = [Node::ZERO; DEPTH];
+        proof.copy_from_slice(&siblings);
+
+        Ok(MerkleProof {
+            proof,
+            leaf: leaf_hash,
+            index: leaf_idx,
+            root,
+        })
+    }
+
+    /// Returns the proof of `leaf_idx` against the tree as it was with `tree_size` leaves,
+    /// so its root is the one returned by `root_at(tree_size)`. Siblings fully on the left
+    /// of that size are read from the store, the one partially covered by it is recomputed,
+    /// and the ones on the right are replaced with empty subtrees.
+    pub fn proof_at(
+        &self,
+        leaf_idx: u64,
+        tree_size: u64,
+    ) -> Result<MerkleProof<DEPTH>, MerkleError> {
+        let num_leaves = self.store.get_num_leaves();
+        if tree_size > num_leaves {
+            return Err(MerkleError::LeafIndexOutOfBounds {
+                index: tree_size,
+                num_leaves,
+            });
+        }
+
+        if leaf_idx >= tree_size {
             return Err(MerkleError::LeafIndexOutOfBounds {
                 index: leaf_idx,
-                num_leaves: 1 << DEPTH as u64,
+                num_leaves: tree_size,
             });
         }
 
-        // Build level/index lists for siblings plus the leaf.
-        // TODO: Can't do arithmetic here with DEPTH meaning there is no
-        // easy way to put this in the stack. Unfortunately the array size
-        // has to be DEPTH + 1 to have a single read. One day Rust will
-        // have const-generic arithmetic.
+        if tree_size == num_leaves {
+            return self.proof(leaf_idx);
+        }
+
+        let edge = self.right_edge_at(tree_size)?;
+
+        // Only the siblings fully on the left of the edge are fetched, plus the leaf itself.
         let mut levels: Vec<u32> = Vec::with_capacity(DEPTH + 1);
         let mut indices: Vec<u64> = Vec::with_capacity(DEPTH + 1);
-
         let mut idx = leaf_idx;
         for level in 0..DEPTH {
             let sibling = idx ^ 1;
-            levels.push(level as u32);
-            indices.push(sibling);
+            if sibling < tree_size >> level {
+                levels.push(level as u32);
+                indices.push(sibling);
+            }
             idx >>= 1;
         }
-
-        // Append the leaf itself at index leaf_idx.
         levels.push(0);
         indices.push(leaf_idx);
 
-        // Batch fetch all requested nodes.
-        let fetched = self.store.get(&levels, &indices)?;
+        let mut fetched = self.store.get(&levels, &indices)?.into_iter();
 
-        // The first DEPTH items are the siblings.
         let mut proof = [Node::ZERO; DEPTH];
-        for (d, opt) in fetched.iter().take(DEPTH).enumerate() {
-            proof[d] = opt.unwrap_or(self.zeros[d]);
+        let mut idx = leaf_idx;
+        for (level, sibling_hash) in proof.iter_mut().enumerate() {
+            let sibling = idx ^ 1;
+            let edge_idx = tree_size >> level;
+            *sibling_hash = match sibling.cmp(&edge_idx) {
+                Ordering::Less => fetched.next().flatten().unwrap_or(self.zeros[level]),
+                Ordering::Equal => edge[level],
+                Ordering::Greater => self.zeros[level],
+            };
+            idx >>= 1;
         }
 
         // The last item is the leaf itself.
-        let leaf_hash = fetched.last().copied().flatten().unwrap_or(self.zeros[0]);
+        let leaf_hash = fetched.next().flatten().unwrap_or(self.zeros[0]);
 
         Ok(MerkleProof {
             proof,
             leaf: leaf_hash,
             index: leaf_idx,
-            root: self.root()?,
+            root: edge[DEPTH],
         })
     }
 
-    pub fn verify_proof(&self, proof: &MerkleProof<DEPTH>) -> Result<bool, MerkleError> {
-        let mut computed_hash = proof.leaf;
-        for (j, sibling_hash) in proof.proof.iter().enumerate() {
-            let (left, right) = if proof.index & (1 << j) == 0 {
-                (computed_hash, *sibling_hash)
-            } else {
-                (*sibling_hash, computed_hash)
-            };
-            computed_hash = self.hasher.hash(&left, &right);
+    /// Returns a single proof for all the leaves at `indices`, which is much smaller than
+    /// one proof per leaf when they share part of their paths. Repeated indices are only
+    /// proven once. See `MultiProof::verify`.
+    pub fn multi_proof(&self, indices: &[u64]) -> Result<MultiProof<DEPTH>, MerkleError> {
+        let mut known: Vec<u64> = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+
+        if let Some(&index) = known.iter().find(|index| !index_fits(DEPTH, **index)) {
+            return Err(MerkleError::LeafIndexOutOfBounds {
+                index,
+                num_leaves: capacity(DEPTH),
+            });
+        }
+
+        // Fetch the leaves, the siblings that can't be computed from them, and the root
+        // in a single read.
+        let mut levels: Vec<u32> = vec![0; known.len()];
+        let mut fetch_indices: Vec<u64> = known.clone();
+        let mut level_known = known.clone();
+        for level in 0..DEPTH {
+            for idx in &level_known {
+                let sibling_idx = idx ^ 1;
+                if level_known.binary_search(&sibling_idx).is_err() {
+                    levels.push(level as u32);
+                    fetch_indices.push(sibling_idx);
+                }
+            }
+            level_known = level_known.iter().map(|idx| idx >> 1).collect();
+            level_known.dedup();
+        }
+        levels.push(DEPTH as u32);
+        fetch_indices.push(0);
+
+        let fetched = self.store.get(&levels, &fetch_indices)?;
+
+        let mut nodes = levels
+            .iter()
+            .zip(fetched)
+            .map(|(level, maybe_node)| maybe_node.unwrap_or(self.zeros[*level as usize]));
+        let leaves: Vec<Node> = nodes.by_ref().take(known.len()).collect();
+        let mut proof: Vec<Node> = nodes.collect();
+        let root = proof.pop().unwrap_or(self.zeros[DEPTH]);
+
+        Ok(MultiProof {
+            indices: known,
+            leaves,
+            proof,
+            root,
+        })
+    }
+
+    /// Returns a proof that the leaves in the range `[start, end)` are exactly a given list,
+    /// only containing the siblings on the boundaries of the range. See `RangeProof::verify`.
+    pub fn range_proof(&self, start: u64, end: u64) -> Result<RangeProof<DEPTH>, MerkleError> {
+        if start >= end {
+            return Err(MerkleError::EmptyRange { start, end });
         }
-        Ok(computed_hash == proof.root)
+        if end > capacity(DEPTH) {
+            return Err(MerkleError::LeafIndexOutOfBounds {
+                index: end,
+                num_leaves: capacity(DEPTH),
+            });
+        }
+
+        // Positions of the siblings on both sides of the range.
+        let mut left: Vec<(u32, u64)> = Vec::with_capacity(DEPTH);
+        let mut right: Vec<(u32, u64)> = Vec::with_capacity(DEPTH);
+        let (mut lo, mut hi) = (start, end - 1);
+        for level in 0..DEPTH {
+            if lo & 1 == 1 {
+                left.push((level as u32, lo ^ 1));
+            }
+            if hi & 1 == 0 {
+                right.push((level as u32, hi ^ 1));
+            }
+            lo >>= 1;
+            hi >>= 1;
+        }
+        let num_left = left.len();
+
+        // Fetch the siblings and the root in a single read.
+        let (levels, indices): (Vec<u32>, Vec<u64>) = left
+            .into_iter()
+            .chain(right)
+            .chain([(DEPTH as u32, 0)])
+            .unzip();
+        let fetched = self.store.get(&levels, &indices)?;
+
+        let mut nodes = levels
+            .iter()
+            .zip(fetched)
+            .map(|(level, maybe_node)| maybe_node.unwrap_or(self.zeros[*level as usize]));
+        let left: Vec<Node> = nodes.by_ref().take(num_left).collect();
+        let mut right: Vec<Node> = nodes.collect();
+        let root = right.pop().unwrap_or(self.zeros[DEPTH]);
+
+        Ok(RangeProof {
+            start,
+            end,
+            left,
+            right,
+            root,
+        })
+    }
+
+    /// Returns a proof that the tree with `new_size` leaves extends the tree with `old_size`
+    /// leaves, this is, that the leaves below `old_size` were not modified in between. See
+    /// `ConsistencyProof::verify`.
+    pub fn consistency_proof(
+        &self,
+        old_size: u64,
+        new_size: u64,
+    ) -> Result<ConsistencyProof<DEPTH>, MerkleError> {
+        if old_size > new_size {
+            return Err(MerkleError::LeafIndexOutOfBounds {
+                index: old_size,
+                num_leaves: new_size,
+            });
+        }
+
+        // Same sizes have the same root, so there is nothing to prove.
+        if old_size == new_size {
+            let num_leaves = self.store.get_num_leaves();
+            if new_size > num_leaves {
+                return Err(MerkleError::LeafIndexOutOfBounds {
+                    index: new_size,
+                    num_leaves,
+                });
+            }
+            return Ok(ConsistencyProof {
+                old_size,
+                new_size,
+                proof: [Node::ZERO; DEPTH],
+                leaf: Node::ZERO,
+            });
+        }
+
+        // The path of the first leaf not in the old tree. Its left siblings are shared by
+        // both trees while the right ones are empty in the old tree.
+        let proof = self.proof_at(old_size, new_size)?;
+
+        Ok(ConsistencyProof {
+            old_size,
+            new_size,
+            proof: proof.proof,
+            leaf: proof.leaf,
+        })
+    }
+
+    pub fn verify_proof(&self, proof: &MerkleProof<DEPTH>) -> Result<bool, MerkleError> {
+        Ok(proof.verify(&self.hasher))
+    }
+
+    pub(crate) fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    pub(crate) fn store(&self) -> &S {
+        &self.store
     }
 
     pub fn num_leaves(&self) -> u64 {
@@ -372,6 +1344,32 @@ mod tests {
         assert_eq!(tree.zeros.last, expected_zeros[32]);
     }
 
+    #[cfg(feature = "memory_store")]
+    #[test]
+    fn test_add_leaves_writes_each_node_once() {
+        let leaves = (0..1_000).map(|_| Node::random()).collect::<Vec<Node>>();
+
+        let mut tree: MerkleTree32 = MerkleTree::default();
+        tree.add_leaves(&leaves[..5]).unwrap();
+        let batch = append_batch(&tree.hasher, &tree.store, &tree.zeros, 32, &leaves[5..]).unwrap();
+
+        // Leaves 5..1000 cover the indices from 5 >> l to 999 >> l at each level l.
+        let expected: u64 = (0..=32).map(|l| (999u64 >> l) - (5 >> l) + 1).sum();
+        assert_eq!(batch.len() as u64, expected);
+        let unique: std::collections::HashSet<(u32, u64)> =
+            batch.iter().map(|(level, idx, _)| (*level, *idx)).collect();
+        assert_eq!(unique.len(), batch.len());
+
+        // Same root as adding the leaves one by one.
+        tree.add_leaves(&leaves[5..]).unwrap();
+        let mut expected_tree: MerkleTree32 = MerkleTree::default();
+        for leaf in &leaves {
+            expected_tree.add_leaves(&[*leaf]).unwrap();
+        }
+        assert_eq!(tree.root().unwrap(), expected_tree.root().unwrap());
+        assert_eq!(batch[batch.len() - 1].2, expected_tree.root().unwrap());
+    }
+
     #[cfg(feature = "memory_store")]
     #[test]
     fn test_tree_full_error() {
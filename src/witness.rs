@@ -0,0 +1,178 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Merkle tree that only tracks the proofs of marked leaves.
+
+use crate::frontier::FrontierTree;
+use crate::hasher::Hasher;
+use crate::tree::MerkleProof;
+use crate::{MerkleError, Node};
+use std::collections::{BTreeMap, HashMap};
+
+struct Witness<const DEPTH: usize> {
+    leaf: Node,
+    // Siblings at each level. The ones on the right are None until they are complete.
+    siblings: [Option<Node>; DEPTH],
+}
+
+/// Append-only Merkle tree that keeps the frontier of the tree plus just enough nodes to
+/// generate up-to-date proofs for the marked leaves, like Zcash incremental witnesses. The
+/// siblings on the left of a marked leaf are known when marking it, and the ones on the
+/// right are recorded once they are complete. Until then, they are computed from the
+/// frontier.
+pub struct WitnessTree<H, const DEPTH: usize>
+where
+    H: Hasher,
+{
+    frontier: FrontierTree<H, DEPTH>,
+    witnesses: BTreeMap<u64, Witness<DEPTH>>,
+    // Marked leaves waiting for each (level, index) right sibling to be complete.
+    pending: HashMap<(usize, u64), Vec<u64>>,
+    last_leaf: Node,
+}
+
+impl<H, const DEPTH: usize> WitnessTree<H, DEPTH>
+where
+    H: Hasher,
+{
+    pub fn new(hasher: H) -> Self {
+        Self {
+            frontier: FrontierTree::new(hasher),
+            witnesses: BTreeMap::new(),
+            pending: HashMap::new(),
+            last_leaf: Node::ZERO,
+        }
+    }
+
+    pub fn add_leaves(&mut self, leaves: &[Node]) -> Result<(), MerkleError> {
+        let witnesses = &mut self.witnesses;
+        let pending = &mut self.pending;
+        let last_leaf = &mut self.last_leaf;
+        self.frontier.add_leaves_with(leaves, |level, index, node| {
+            if level == 0 {
+                *last_leaf = *node;
+            }
+            let Some(marked) = pending.remove(&(level, index)) else {
+                return;
+            };
+            for leaf_idx in marked {
+                if let Some(witness) = witnesses.get_mut(&leaf_idx) {
+                    witness.siblings[level] = Some(*node);
+                }
+            }
+        })
+    }
+
+    /// Marks the last added leaf so its proof can be generated later on. Returns its index,
+    /// or None if the tree is empty.
+    pub fn mark(&mut self) -> Option<u64> {
+        let index = self.frontier.num_leaves().checked_sub(1)?;
+        if self.witnesses.contains_key(&index) {
+            return Some(index);
+        }
+
+        let mut siblings = [None; DEPTH];
+        for (level, sibling) in siblings.iter_mut().enumerate() {
+            let idx = index >> level;
+            if idx & 1 == 1 {
+                *sibling = Some(self.frontier.branch(level));
+            } else {
+                self.pending
+                    .entry((level, idx ^ 1))
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        self.witnesses.insert(
+            index,
+            Witness {
+                leaf: self.last_leaf,
+                siblings,
+            },
+        );
+        Some(index)
+    }
+
+    /// Stops tracking the proof of the leaf at `index`, releasing its nodes. Returns false
+    /// if it wasn't marked.
+    pub fn unmark(&mut self, index: u64) -> bool {
+        let Some(witness) = self.witnesses.remove(&index) else {
+            return false;
+        };
+
+        for (level, sibling) in witness.siblings.iter().enumerate() {
+            let idx = index >> level;
+            if idx & 1 == 1 || sibling.is_some() {
+                continue;
+            }
+            if let Some(marked) = self.pending.get_mut(&(level, idx ^ 1)) {
+                marked.retain(|i| *i != index);
+                if marked.is_empty() {
+                    self.pending.remove(&(level, idx ^ 1));
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Returns the indices of the marked leaves, sorted.
+    pub fn marked(&self) -> impl Iterator<Item = u64> + '_ {
+        self.witnesses.keys().copied()
+    }
+
+    /// Returns the proof of the marked leaf at `index` against the current root, or None
+    /// if it isn't marked.
+    pub fn proof(&self, index: u64) -> Option<MerkleProof<DEPTH>> {
+        let witness = self.witnesses.get(&index)?;
+        let num_leaves = self.frontier.num_leaves();
+
+        let mut proof = [Node::ZERO; DEPTH];
+        for (level, sibling) in witness.siblings.iter().enumerate() {
+            proof[level] = match sibling {
+                Some(node) => *node,
+                // The sibling covering the next leaf is partially filled, and the ones
+                // after it are empty.
+                None if (index >> level) ^ 1 == num_leaves >> level => {
+                    self.frontier.edge_node(level)
+                }
+                None => self.frontier.zeros()[level],
+            };
+        }
+
+        Some(MerkleProof {
+            proof,
+            leaf: witness.leaf,
+            index,
+            root: self.root(),
+        })
+    }
+
+    pub fn root(&self) -> Node {
+        self.frontier.root()
+    }
+
+    pub fn num_leaves(&self) -> u64 {
+        self.frontier.num_leaves()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hasher::Keccak256Hasher;
+
+    #[test]
+    fn test_witness_unmark_releases() {
+        let mut witness = WitnessTree::<Keccak256Hasher, 8>::new(Keccak256Hasher);
+        assert_eq!(witness.mark(), None);
+
+        witness.add_leaves(&[Node::ZERO]).unwrap();
+        assert_eq!(witness.mark(), Some(0));
+        assert!(!witness.pending.is_empty());
+
+        assert!(witness.unmark(0));
+        assert!(witness.witnesses.is_empty());
+        assert!(witness.pending.is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+#![cfg(feature = "memory_store")]
+
+mod common;
+
+use common::leaves;
+use rs_merkle_tree::hasher::Keccak256Hasher;
+use rs_merkle_tree::stores::{BlockingStore, MemoryStore};
+use rs_merkle_tree::{AsyncMerkleTree, MerkleTree};
+
+#[tokio::test]
+async fn test_async_matches_merkle_tree() {
+    let leaves = leaves(50);
+
+    let mut tree: MerkleTree<Keccak256Hasher, MemoryStore, 32> =
+        MerkleTree::new(Keccak256Hasher, MemoryStore::new());
+    let mut async_tree: AsyncMerkleTree<Keccak256Hasher, _, 32> =
+        AsyncMerkleTree::new(Keccak256Hasher, BlockingStore::new(MemoryStore::new()));
+    assert_eq!(async_tree.root().await.unwrap(), tree.root().unwrap());
+
+    for chunk in leaves.chunks(9) {
+        tree.add_leaves(chunk).unwrap();
+        async_tree.add_leaves(chunk).await.unwrap();
+        assert_eq!(async_tree.root().await.unwrap(), tree.root().unwrap());
+    }
+    assert_eq!(async_tree.num_leaves().await, 50);
+
+    for i in [0, 17, 49, 50] {
+        let proof = async_tree.proof(i).await.unwrap();
+        let expected = tree.proof(i).unwrap();
+        assert_eq!(proof.proof, expected.proof);
+        assert_eq!(proof.leaf, expected.leaf);
+        assert_eq!(proof.root, expected.root);
+        assert!(async_tree.verify_proof(&proof));
+    }
+}
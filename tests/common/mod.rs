@@ -0,0 +1,16 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+//! Fixtures shared by the integration tests. Each test binary only uses some of them.
+#![allow(dead_code)]
+
+use rs_merkle_tree::{to_node, Node};
+
+/// Returns the node holding `i` as a big-endian number.
+pub fn node(i: u64) -> Node {
+    to_node!(format!("0x{:064x}", i).as_str())
+}
+
+/// Returns the leaves `1..=n`, see `node`.
+pub fn leaves(n: u64) -> Vec<Node> {
+    (1..=n).map(node).collect()
+}
@@ -0,0 +1,80 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+#![cfg(feature = "memory_store")]
+
+mod common;
+
+use common::leaves;
+use rs_merkle_tree::hasher::{Keccak256Hasher, PoseidonHasher};
+use rs_merkle_tree::stores::MemoryStore;
+use rs_merkle_tree::{DynMerkleTree, MerkleError, MerkleTree, Node, Store};
+
+#[test]
+fn test_dyn_matches_merkle_tree() {
+    let leaves = leaves(50);
+
+    let mut tree: MerkleTree<PoseidonHasher, MemoryStore, 20> =
+        MerkleTree::new(PoseidonHasher, MemoryStore::new());
+    let mut dyn_tree = DynMerkleTree::new(PoseidonHasher, MemoryStore::new(), 20);
+    assert_eq!(dyn_tree.root().unwrap(), tree.root().unwrap());
+
+    for chunk in leaves.chunks(9) {
+        tree.add_leaves(chunk).unwrap();
+        dyn_tree.add_leaves(chunk).unwrap();
+        assert_eq!(dyn_tree.root().unwrap(), tree.root().unwrap());
+    }
+
+    for i in [0, 17, 49, 50] {
+        let proof = dyn_tree.proof(i).unwrap();
+        let expected = tree.proof(i).unwrap();
+        assert_eq!(proof.proof, expected.proof);
+        assert_eq!(proof.leaf, expected.leaf);
+        assert_eq!(proof.root, expected.root);
+        assert!(dyn_tree.verify_proof(&proof));
+    }
+}
+
+#[test]
+fn test_dyn_depths_side_by_side() {
+    let trees: Vec<DynMerkleTree<Keccak256Hasher, MemoryStore>> = [20, 26, 32]
+        .into_iter()
+        .map(|depth| DynMerkleTree::new(Keccak256Hasher, MemoryStore::new(), depth))
+        .collect();
+
+    for (mut tree, depth) in trees.into_iter().zip([20, 26, 32]) {
+        assert_eq!(tree.depth(), depth);
+        tree.add_leaves(&[Node::random(), Node::random()]).unwrap();
+        let proof = tree.proof(1).unwrap();
+        assert_eq!(proof.proof.len(), depth);
+        assert!(tree.verify_proof(&proof));
+    }
+
+    let mut small = DynMerkleTree::new(Keccak256Hasher, MemoryStore::new(), 2);
+    assert!(matches!(
+        small.add_leaves(&[Node::ZERO; 5]),
+        Err(MerkleError::TreeFull {
+            depth: 2,
+            capacity: 4
+        })
+    ));
+}
+
+#[test]
+fn test_dyn_try_new_errors() {
+    for depth in [0, 65] {
+        assert!(matches!(
+            DynMerkleTree::try_new(Keccak256Hasher, MemoryStore::new(), depth),
+            Err(MerkleError::InvalidDepth { depth: d }) if d == depth
+        ));
+    }
+
+    let mut store = MemoryStore::new();
+    store.put(&[(0, 4, Node::ZERO)]).unwrap();
+    assert!(matches!(
+        DynMerkleTree::try_new(Keccak256Hasher, store, 2),
+        Err(MerkleError::CapacityExceeded {
+            num_leaves: 5,
+            capacity: 4
+        })
+    ));
+}
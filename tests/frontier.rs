@@ -0,0 +1,120 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+#![cfg(feature = "memory_store")]
+
+mod common;
+
+use common::{leaves, node};
+use rs_merkle_tree::hasher::{Keccak256Hasher, PoseidonHasher};
+use rs_merkle_tree::stores::MemoryStore;
+use rs_merkle_tree::{FrontierParts, FrontierTree, MerkleError, MerkleTree, Node};
+
+#[test]
+fn test_frontier_same_roots() {
+    let leaves = leaves(300);
+
+    let mut frontier = FrontierTree::<PoseidonHasher, 20>::new(PoseidonHasher);
+    let mut tree =
+        MerkleTree::<PoseidonHasher, MemoryStore, 20>::new(PoseidonHasher, MemoryStore::new());
+    assert_eq!(frontier.root(), tree.root().unwrap());
+
+    for chunk in leaves.chunks(7) {
+        frontier.add_leaves(chunk).unwrap();
+        tree.add_leaves(chunk).unwrap();
+        assert_eq!(frontier.root(), tree.root().unwrap());
+    }
+    assert_eq!(frontier.num_leaves(), 300);
+
+    // Converting it into a full tree needs all the leaves.
+    let full = frontier
+        .into_merkle_tree(MemoryStore::new(), &leaves)
+        .unwrap();
+    assert_eq!(full.root().unwrap(), tree.root().unwrap());
+}
+
+#[test]
+fn test_frontier_full() {
+    let leaves = leaves(8);
+
+    let mut frontier = FrontierTree::<Keccak256Hasher, 3>::new(Keccak256Hasher);
+    frontier.add_leaves(&leaves).unwrap();
+
+    let mut tree =
+        MerkleTree::<Keccak256Hasher, MemoryStore, 3>::new(Keccak256Hasher, MemoryStore::new());
+    tree.add_leaves(&leaves).unwrap();
+    assert_eq!(frontier.root(), tree.root().unwrap());
+
+    // It errors since the tree is full
+    assert!(frontier.add_leaves(&[Node::ZERO]).is_err());
+
+    // Replaying different leaves is detected.
+    assert!(matches!(
+        frontier.into_merkle_tree(MemoryStore::new(), &leaves[..7]),
+        Err(MerkleError::LeafCountMismatch {
+            expected: 8,
+            actual: 7
+        })
+    ));
+}
+
+#[test]
+fn test_frontier_wrong_leaves() {
+    let mut frontier = FrontierTree::<Keccak256Hasher, 3>::new(Keccak256Hasher);
+    frontier.add_leaves(&[Node::ZERO, Node::ZERO]).unwrap();
+
+    let leaves = [Node::ZERO, node(1)];
+    assert!(matches!(
+        frontier.into_merkle_tree(MemoryStore::new(), &leaves),
+        Err(MerkleError::RootMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_frontier_parts() {
+    let leaves = leaves(13);
+
+    let mut frontier = FrontierTree::<PoseidonHasher, 4>::new(PoseidonHasher);
+    frontier.add_leaves(&leaves[..10]).unwrap();
+    let root = frontier.root();
+
+    // The restored tree keeps growing like the original.
+    let parts = frontier.into_parts();
+    let mut restored = FrontierTree::from_parts(PoseidonHasher, parts.clone()).unwrap();
+    assert_eq!(restored.num_leaves(), 10);
+    assert_eq!(restored.root(), root);
+    restored.add_leaves(&leaves[10..]).unwrap();
+
+    let mut expected = FrontierTree::<PoseidonHasher, 4>::new(PoseidonHasher);
+    expected.add_leaves(&leaves).unwrap();
+    assert_eq!(restored.root(), expected.root());
+
+    // Also once full.
+    expected
+        .add_leaves(&[node(14), node(15), node(16)])
+        .unwrap();
+    let root = expected.root();
+    let full = expected.into_parts();
+    assert!(full.full_root.is_some());
+    let restored = FrontierTree::<PoseidonHasher, 4>::from_parts(PoseidonHasher, full).unwrap();
+    assert_eq!(restored.root(), root);
+
+    let too_many = FrontierParts {
+        num_leaves: 17,
+        ..parts.clone()
+    };
+    assert!(matches!(
+        FrontierTree::from_parts(PoseidonHasher, too_many),
+        Err(MerkleError::CapacityExceeded {
+            num_leaves: 17,
+            capacity: 16
+        })
+    ));
+    let not_full = FrontierParts {
+        full_root: Some(root),
+        ..parts
+    };
+    assert!(matches!(
+        FrontierTree::from_parts(PoseidonHasher, not_full),
+        Err(MerkleError::InvalidFrontier { num_leaves: 10 })
+    ));
+}
@@ -0,0 +1,31 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+mod common;
+
+use common::leaves;
+use rs_merkle_tree::hasher::{Hasher, Keccak256Hasher, PoseidonHasher};
+use rs_merkle_tree::to_node;
+
+#[test]
+fn test_hash_many() {
+    let nodes = leaves(4);
+
+    // Same as hashing two nodes
+    for hasher in [&Keccak256Hasher as &dyn Hasher, &PoseidonHasher] {
+        assert_eq!(
+            hasher.hash_many(&nodes[..2]),
+            hasher.hash(&nodes[0], &nodes[1])
+        );
+    }
+
+    assert_eq!(
+        PoseidonHasher.hash_many(&nodes),
+        to_node!("0x299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465")
+    );
+}
+
+#[test]
+#[should_panic(expected = "poseidon takes 1 to 12 inputs, got 13")]
+fn test_hash_many_too_many_inputs() {
+    PoseidonHasher.hash_many(&leaves(13));
+}
@@ -0,0 +1,139 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+#![cfg(any(feature = "memory_store", feature = "sqlite_store"))]
+
+mod common;
+
+use common::node;
+use rs_merkle_tree::hasher::Keccak256Hasher;
+use rs_merkle_tree::IndexedMerkleTree;
+
+#[cfg(feature = "sqlite_store")]
+use rs_merkle_tree::stores::SqliteStore;
+#[cfg(feature = "memory_store")]
+use rs_merkle_tree::{
+    indexed::IndexedLeaf, stores::MemoryStore, tree::MerkleProof, MerkleError, MerkleTree, Node,
+};
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_indexed_insert() {
+    let hasher = Keccak256Hasher;
+    let mut tree: IndexedMerkleTree<Keccak256Hasher, MemoryStore, 16> =
+        IndexedMerkleTree::new(Keccak256Hasher, MemoryStore::new());
+    assert_eq!(tree.num_leaves(), 1);
+    assert_eq!(
+        tree.leaf(0).unwrap(),
+        IndexedLeaf {
+            value: Node::ZERO,
+            next_index: 0,
+            next_value: Node::ZERO,
+        }
+    );
+
+    for v in [30, 10, 20] {
+        let old_root = tree.root().unwrap();
+        let witness = tree.insert(node(v)).unwrap();
+
+        // The low leaf is proven against the old root, and the new leaf is appended
+        // to an empty slot.
+        assert!(witness.low_leaf_proof.verify(&hasher));
+        assert_eq!(witness.low_leaf_proof.root, old_root);
+        assert_eq!(witness.low_leaf_proof.leaf, witness.low_leaf.hash(&hasher));
+        assert!(witness.new_leaf_proof.verify(&hasher));
+        assert_eq!(witness.new_leaf_proof.leaf, Node::ZERO);
+
+        let new_proof = MerkleProof {
+            leaf: witness.new_leaf.hash(&hasher),
+            root: tree.root().unwrap(),
+            ..witness.new_leaf_proof
+        };
+        assert!(new_proof.verify(&hasher));
+    }
+
+    // The leaves form a sorted linked list.
+    let expected = [(0, 2, 10), (30, 0, 0), (10, 3, 20), (20, 1, 30)];
+    for (index, (v, next_index, next_value)) in expected.into_iter().enumerate() {
+        assert_eq!(
+            tree.leaf(index as u64).unwrap(),
+            IndexedLeaf {
+                value: node(v),
+                next_index,
+                next_value: node(next_value),
+            }
+        );
+    }
+
+    // Same root as a plain tree with the hashes of the leaves.
+    let mut plain: MerkleTree<Keccak256Hasher, MemoryStore, 16> =
+        MerkleTree::new(Keccak256Hasher, MemoryStore::new());
+    let hashes = (0..4)
+        .map(|index| tree.leaf(index).unwrap().hash(&hasher))
+        .collect::<Vec<Node>>();
+    plain.add_leaves(&hashes).unwrap();
+    assert_eq!(tree.root().unwrap(), plain.root().unwrap());
+
+    assert_eq!(tree.index_of(&node(20)), Some(3));
+    assert_eq!(tree.low_leaf(&node(25)).unwrap().0, 3);
+
+    assert!(matches!(
+        tree.insert(node(20)),
+        Err(MerkleError::KeyExists { .. })
+    ));
+    assert!(matches!(
+        tree.insert(Node::ZERO),
+        Err(MerkleError::KeyExists { .. })
+    ));
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_indexed_non_membership() {
+    let hasher = Keccak256Hasher;
+    let mut tree: IndexedMerkleTree<Keccak256Hasher, MemoryStore, 16> =
+        IndexedMerkleTree::new(Keccak256Hasher, MemoryStore::new());
+    for v in [30, 10, 20] {
+        tree.insert(node(v)).unwrap();
+    }
+
+    // Between two values and after the last one.
+    for v in [15, 31] {
+        let proof = tree.non_membership_proof(&node(v)).unwrap();
+        assert!(proof.verify(&hasher));
+        assert_eq!(proof.proof.root, tree.root().unwrap());
+    }
+    assert!(tree.non_membership_proof(&node(20)).is_err());
+
+    // The low leaf of another value doesn't prove it's missing.
+    let mut proof = tree.non_membership_proof(&node(15)).unwrap();
+    proof.value = node(25);
+    assert!(!proof.verify(&hasher));
+}
+
+#[cfg(feature = "sqlite_store")]
+#[test]
+fn test_indexed_persisted() {
+    let temp_file = temp_file::TempFile::with_suffix("_sqlite.db").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let root = {
+        let mut tree: IndexedMerkleTree<Keccak256Hasher, SqliteStore, 16> =
+            IndexedMerkleTree::try_new(Keccak256Hasher, SqliteStore::new(path)).unwrap();
+        tree.insert(node(10)).unwrap();
+        tree.insert(node(5)).unwrap();
+        // More than a page of preimages to load.
+        for i in 0..1_100 {
+            tree.insert(node(1_000 + i)).unwrap();
+        }
+        tree.root().unwrap()
+    };
+
+    // The values are loaded when reopening the store.
+    let mut tree: IndexedMerkleTree<Keccak256Hasher, SqliteStore, 16> =
+        IndexedMerkleTree::try_new(Keccak256Hasher, SqliteStore::new(path)).unwrap();
+    assert_eq!(tree.root().unwrap(), root);
+    assert_eq!(tree.index_of(&node(5)), Some(2));
+    assert_eq!(tree.index_of(&node(2_099)), Some(1_102));
+    let witness = tree.insert(node(7)).unwrap();
+    assert_eq!(witness.low_leaf.value, node(5));
+}
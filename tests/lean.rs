@@ -0,0 +1,160 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+#![cfg(feature = "memory_store")]
+
+mod common;
+
+use common::{leaves, node};
+use rs_merkle_tree::hasher::{Hasher, PoseidonHasher};
+use rs_merkle_tree::stores::MemoryStore;
+use rs_merkle_tree::{to_node, LeanMerkleTree, MerkleError, Node};
+
+#[test]
+fn test_lean_root() {
+    let hasher = PoseidonHasher;
+    let mut tree = LeanMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    assert_eq!(tree.root().unwrap(), Node::ZERO);
+
+    let l = leaves(5);
+    tree.add_leaves(&l[..1]).unwrap();
+    assert_eq!(tree.depth(), 0);
+    assert_eq!(tree.root().unwrap(), l[0]);
+
+    // Root of zk-kit LeanIMT with leaves [1, 2]
+    tree.add_leaves(&l[1..2]).unwrap();
+    assert_eq!(tree.depth(), 1);
+    assert_eq!(
+        tree.root().unwrap(),
+        to_node!("0x115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a")
+    );
+
+    // The last leaf is carried up without hashing.
+    tree.add_leaves(&l[2..]).unwrap();
+    let h12 = hasher.hash(&l[0], &l[1]);
+    let h34 = hasher.hash(&l[2], &l[3]);
+    assert_eq!(tree.depth(), 3);
+    assert_eq!(tree.num_leaves(), 5);
+    assert_eq!(
+        tree.root().unwrap(),
+        hasher.hash(&hasher.hash(&h12, &h34), &l[4])
+    );
+}
+
+#[test]
+fn test_lean_batches() {
+    let l = leaves(37);
+
+    let mut one_by_one = LeanMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    for leaf in &l {
+        one_by_one.add_leaves(&[*leaf]).unwrap();
+    }
+
+    let mut batched = LeanMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    for chunk in l.chunks(6) {
+        batched.add_leaves(chunk).unwrap();
+    }
+    assert_eq!(batched.root().unwrap(), one_by_one.root().unwrap());
+}
+
+#[test]
+fn test_lean_proof() {
+    let hasher = PoseidonHasher;
+    let l = leaves(5);
+    let mut tree = LeanMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    tree.add_leaves(&l).unwrap();
+
+    // The last leaf only has a sibling at the root level.
+    let proof = tree.proof(4).unwrap();
+    assert_eq!(proof.leaf, l[4]);
+    assert_eq!(proof.index, 1);
+    assert_eq!(
+        proof.siblings,
+        [hasher.hash(&hasher.hash(&l[0], &l[1]), &hasher.hash(&l[2], &l[3]))]
+    );
+    assert!(tree.verify_proof(&proof));
+
+    let proof = tree.proof(2).unwrap();
+    assert_eq!(proof.index, 0b010);
+    assert_eq!(proof.siblings.len(), 3);
+    for i in 0..5 {
+        assert!(tree.proof(i).unwrap().verify(&hasher));
+    }
+
+    // Tampered proofs fail.
+    let mut proof = tree.proof(3).unwrap();
+    proof.index ^= 1;
+    assert!(!proof.verify(&hasher));
+
+    assert!(matches!(
+        tree.proof(5),
+        Err(MerkleError::LeafIndexOutOfBounds {
+            index: 5,
+            num_leaves: 5
+        })
+    ));
+}
+
+#[test]
+fn test_lean_update_leaf() {
+    let mut l = leaves(11);
+    let mut tree = LeanMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    tree.add_leaves(&l).unwrap();
+
+    for (index, value) in [(0, 100), (10, 101), (7, 102)] {
+        l[index] = node(value);
+        tree.update_leaf(index as u64, l[index]).unwrap();
+
+        let mut expected = LeanMerkleTree::new(PoseidonHasher, MemoryStore::new());
+        expected.add_leaves(&l).unwrap();
+        assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+        assert_eq!(tree.proof(index as u64).unwrap().leaf, l[index]);
+    }
+
+    assert!(tree.update_leaf(11, Node::ZERO).is_err());
+}
+
+#[test]
+fn test_lean_unbalanced_vectors() {
+    let hasher = PoseidonHasher;
+
+    // LeanIMT with leaves [1, 2, 3]: the third leaf is carried up to the root level.
+    let l = leaves(3);
+    let mut tree = LeanMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    tree.add_leaves(&l).unwrap();
+    assert_eq!(
+        tree.root().unwrap(),
+        to_node!("0x1e8c05563aa22ff357008db7a754ea0404695de07b950ce845b872a8bcff2ca9")
+    );
+
+    let proof = tree.proof(2).unwrap();
+    assert_eq!(proof.index, 1);
+    assert_eq!(
+        proof.siblings,
+        [to_node!(
+            "0x115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"
+        )]
+    );
+    let proof = tree.proof(0).unwrap();
+    assert_eq!(proof.index, 0);
+    assert_eq!(proof.siblings, [l[1], l[2]]);
+    assert!(proof.verify(&hasher));
+
+    // LeanIMT with leaves [1, 2, 3, 4, 5]
+    let l = leaves(5);
+    let mut tree = LeanMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    tree.add_leaves(&l).unwrap();
+    assert_eq!(
+        tree.root().unwrap(),
+        to_node!("0x1973be9a0ac928df30c68c1698876c310c8246a3f215d33764045ec9da859b08")
+    );
+
+    let proof = tree.proof(4).unwrap();
+    assert_eq!(proof.index, 1);
+    assert_eq!(
+        proof.siblings,
+        [to_node!(
+            "0x075d30e28d48842bd6c1044b68f982d586e2892ae91c77f8f56111d8f55070ed"
+        )]
+    );
+    assert!(proof.verify(&hasher));
+}
@@ -0,0 +1,130 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+#![cfg(feature = "memory_store")]
+
+mod common;
+
+use common::leaves;
+use rs_merkle_tree::hasher::{Hasher, Keccak256Hasher, PoseidonHasher};
+use rs_merkle_tree::stores::MemoryStore;
+use rs_merkle_tree::{MerkleError, MerkleTree, NaryMerkleTree, Node};
+
+#[test]
+fn test_nary_root() {
+    let hasher = PoseidonHasher;
+    let mut tree: NaryMerkleTree<PoseidonHasher, MemoryStore, 4, 2> =
+        NaryMerkleTree::new(PoseidonHasher, MemoryStore::new());
+
+    let zero = hasher.hash_many(&[Node::ZERO; 4]);
+    assert_eq!(tree.root().unwrap(), hasher.hash_many(&[zero; 4]));
+
+    let l = leaves(5);
+    tree.add_leaves(&l).unwrap();
+    let expected = hasher.hash_many(&[
+        hasher.hash_many(&l[..4]),
+        hasher.hash_many(&[l[4], Node::ZERO, Node::ZERO, Node::ZERO]),
+        zero,
+        zero,
+    ]);
+    assert_eq!(tree.root().unwrap(), expected);
+
+    assert!(matches!(
+        tree.add_leaves(&leaves(12)),
+        Err(MerkleError::TreeFull {
+            depth: 2,
+            capacity: 16
+        })
+    ));
+}
+
+#[test]
+fn test_nary_unsupported_arity() {
+    // Poseidon takes up to 12 inputs, while keccak has no limit.
+    let tree: NaryMerkleTree<PoseidonHasher, MemoryStore, 12, 2> =
+        NaryMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    assert!(tree.root().is_ok());
+    assert!(matches!(
+        NaryMerkleTree::<PoseidonHasher, MemoryStore, 13, 2>::try_new(
+            PoseidonHasher,
+            MemoryStore::new()
+        ),
+        Err(MerkleError::UnsupportedArity { arity: 13, max: 12 })
+    ));
+    assert!(
+        NaryMerkleTree::<Keccak256Hasher, MemoryStore, 13, 2>::try_new(
+            Keccak256Hasher,
+            MemoryStore::new()
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_nary_binary_matches_merkle_tree() {
+    let l = leaves(37);
+    let mut nary: NaryMerkleTree<Keccak256Hasher, MemoryStore, 2, 16> =
+        NaryMerkleTree::new(Keccak256Hasher, MemoryStore::new());
+    let mut tree: MerkleTree<Keccak256Hasher, MemoryStore, 16> =
+        MerkleTree::new(Keccak256Hasher, MemoryStore::new());
+
+    for chunk in l.chunks(5) {
+        nary.add_leaves(chunk).unwrap();
+        tree.add_leaves(chunk).unwrap();
+        assert_eq!(nary.root().unwrap(), tree.root().unwrap());
+    }
+
+    let proof = nary.proof(13).unwrap();
+    let expected = tree.proof(13).unwrap();
+    for (siblings, sibling) in proof.proof.iter().zip(expected.proof) {
+        assert_eq!(siblings, &[sibling]);
+    }
+}
+
+#[test]
+fn test_nary_proof() {
+    let l = leaves(100);
+    let mut one_by_one: NaryMerkleTree<PoseidonHasher, MemoryStore, 4, 5> =
+        NaryMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    for leaf in &l {
+        one_by_one.add_leaves(&[*leaf]).unwrap();
+    }
+
+    let mut tree: NaryMerkleTree<PoseidonHasher, MemoryStore, 4, 5> =
+        NaryMerkleTree::new(PoseidonHasher, MemoryStore::new());
+    for chunk in l.chunks(7) {
+        tree.add_leaves(chunk).unwrap();
+    }
+    assert_eq!(tree.root().unwrap(), one_by_one.root().unwrap());
+    assert_eq!(tree.num_leaves(), 100);
+
+    for i in [0, 3, 4, 63, 64, 99, 100, 1023] {
+        let proof = tree.proof(i).unwrap();
+        assert!(proof.proof.iter().all(|siblings| siblings.len() == 3));
+        assert_eq!(proof.leaf, l.get(i as usize).copied().unwrap_or(Node::ZERO));
+        assert!(tree.verify_proof(&proof));
+    }
+
+    let mut proof = tree.proof(5).unwrap();
+    proof.index = 6;
+    assert!(!tree.verify_proof(&proof));
+    assert!(tree.proof(1024).is_err());
+}
+
+#[test]
+fn test_nary_proof_last_indices() {
+    // 4^32 and 3^41 leaves don't fit in a u64, so every index has a proof.
+    let tree: NaryMerkleTree<Keccak256Hasher, MemoryStore, 4, 32> =
+        NaryMerkleTree::new(Keccak256Hasher, MemoryStore::new());
+    for i in [u64::MAX - 1, u64::MAX] {
+        let proof = tree.proof(i).unwrap();
+        assert_eq!(proof.leaf, Node::ZERO);
+        assert!(tree.verify_proof(&proof));
+    }
+
+    // The last group of leaves is cut short by u64::MAX.
+    let tree: NaryMerkleTree<Keccak256Hasher, MemoryStore, 3, 41> =
+        NaryMerkleTree::new(Keccak256Hasher, MemoryStore::new());
+    let proof = tree.proof(u64::MAX).unwrap();
+    assert!(proof.proof.iter().all(|siblings| siblings.len() == 2));
+    assert!(tree.verify_proof(&proof));
+}
@@ -0,0 +1,94 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+#![cfg(any(feature = "memory_store", feature = "sqlite_store"))]
+
+use rs_merkle_tree::hasher::Keccak256Hasher;
+#[cfg(feature = "memory_store")]
+use rs_merkle_tree::stores::MemoryStore;
+#[cfg(feature = "sqlite_store")]
+use rs_merkle_tree::stores::{MutexStore, SqliteStore};
+#[cfg(feature = "memory_store")]
+use rs_merkle_tree::MerkleError;
+use rs_merkle_tree::{MerkleTree, Node, SharedMerkleTree, Store};
+
+fn check_concurrent_readers_and_writer<S>(store: S)
+where
+    S: Store + Send + Sync,
+{
+    let tree: SharedMerkleTree<Keccak256Hasher, S, 32> =
+        MerkleTree::new(Keccak256Hasher, store).into();
+
+    std::thread::scope(|scope| {
+        let writer = tree.clone();
+        scope.spawn(move || {
+            for _ in 0..100 {
+                writer
+                    .add_leaves(&[Node::random(), Node::random()])
+                    .unwrap();
+            }
+        });
+
+        for _ in 0..4 {
+            let reader = tree.clone();
+            scope.spawn(move || {
+                for _ in 0..200 {
+                    let num_leaves = reader.num_leaves().unwrap();
+                    let proof = reader.proof(num_leaves.saturating_sub(1)).unwrap();
+                    assert!(reader.verify_proof(&proof).unwrap());
+
+                    // The root only changes between reads.
+                    reader.read(|tree| {
+                        let proof = tree.proof(tree.num_leaves() / 2).unwrap();
+                        assert_eq!(proof.root, tree.root().unwrap());
+                    });
+                }
+            });
+        }
+    });
+
+    assert_eq!(tree.num_leaves().unwrap(), 200);
+    tree.write(|tree| tree.truncate(100)).unwrap();
+    assert_eq!(tree.num_leaves().unwrap(), 100);
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_concurrent_readers_and_writer() {
+    check_concurrent_readers_and_writer(MemoryStore::new());
+}
+
+// `SqliteStore` is only `Send`, so it's shared through a `MutexStore`.
+#[cfg(feature = "sqlite_store")]
+#[test]
+fn test_concurrent_readers_and_writer_sqlite() {
+    let temp_file = temp_file::TempFile::with_suffix("_sqlite.db").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+    check_concurrent_readers_and_writer(MutexStore::new(SqliteStore::new(path)));
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_poisoned_lock() {
+    let tree: SharedMerkleTree<Keccak256Hasher, MemoryStore, 32> =
+        MerkleTree::new(Keccak256Hasher, MemoryStore::new()).into();
+
+    // A writer panicking between two writes poisons the tree for the other handles.
+    let writer = tree.clone();
+    let result = std::panic::catch_unwind(move || {
+        writer.write(|tree| {
+            tree.add_leaves(&[Node::random()]).unwrap();
+            panic!("writer failed");
+        })
+    });
+    assert!(result.is_err());
+    assert!(matches!(tree.num_leaves(), Err(MerkleError::LockPoisoned)));
+    assert!(matches!(tree.root(), Err(MerkleError::LockPoisoned)));
+    assert!(matches!(tree.proof(0), Err(MerkleError::LockPoisoned)));
+    assert!(matches!(
+        tree.add_leaves(&[Node::random()]),
+        Err(MerkleError::LockPoisoned)
+    ));
+
+    // Only the closures can't report it.
+    assert!(std::panic::catch_unwind(|| tree.read(|tree| tree.num_leaves())).is_err());
+}
@@ -0,0 +1,164 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+#![cfg(any(
+    feature = "memory_store",
+    feature = "sled_store",
+    feature = "sqlite_store"
+))]
+
+mod common;
+
+use rs_merkle_tree::hasher::Keccak256Hasher;
+use rs_merkle_tree::{to_node, SparseMerkleTree};
+
+#[cfg(feature = "memory_store")]
+use common::node;
+#[cfg(feature = "sled_store")]
+use rs_merkle_tree::stores::SledStore;
+#[cfg(feature = "sqlite_store")]
+use rs_merkle_tree::stores::SqliteStore;
+#[cfg(any(feature = "sled_store", feature = "sqlite_store"))]
+use rs_merkle_tree::Store;
+#[cfg(feature = "memory_store")]
+use rs_merkle_tree::{hasher::PoseidonHasher, stores::MemoryStore, tree::Zeros, MerkleError, Node};
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_sparse_insert_update_delete() {
+    let mut tree = SparseMerkleTree::new(Keccak256Hasher, MemoryStore::new());
+    let empty_root = tree.root().unwrap();
+    assert_eq!(empty_root, Zeros::<256>::new(&Keccak256Hasher)[256]);
+
+    let key1 = to_node!("0x0000000000000000000000000000000000000000000000000000000000000001");
+    let key2 = to_node!("0x8000000000000000000000000000000000000000000000000000000000000001");
+    let value1 = to_node!("0x0000000000000000000000000000000000000000000000000000000000000011");
+    let value2 = to_node!("0x0000000000000000000000000000000000000000000000000000000000000022");
+
+    tree.insert(&key1, value1).unwrap();
+    tree.insert(&key2, value2).unwrap();
+    assert_eq!(tree.num_leaves().unwrap(), 2);
+    assert_eq!(tree.get(&key1).unwrap(), Some(value1));
+    assert_eq!(tree.get(&key2).unwrap(), Some(value2));
+    let root = tree.root().unwrap();
+
+    // The root doesn't depend on the insertion order.
+    let mut other = SparseMerkleTree::new(Keccak256Hasher, MemoryStore::new());
+    other.insert(&key2, value2).unwrap();
+    other.insert(&key1, value1).unwrap();
+    assert_eq!(other.root().unwrap(), root);
+
+    assert!(matches!(
+        tree.insert(&key1, value2),
+        Err(MerkleError::KeyExists { .. })
+    ));
+    assert!(matches!(
+        tree.insert(&Node::ZERO, Node::ZERO),
+        Err(MerkleError::ZeroValue { .. })
+    ));
+
+    tree.update(&key1, value2).unwrap();
+    assert_eq!(tree.get(&key1).unwrap(), Some(value2));
+    assert_ne!(tree.root().unwrap(), root);
+    tree.update(&key1, value1).unwrap();
+    assert_eq!(tree.root().unwrap(), root);
+
+    // Deleting all keys brings back the empty root.
+    tree.delete(&key1).unwrap();
+    assert_eq!(tree.get(&key1).unwrap(), None);
+    assert!(matches!(
+        tree.delete(&key1),
+        Err(MerkleError::KeyNotFound { .. })
+    ));
+    assert!(matches!(
+        tree.update(&key1, value1),
+        Err(MerkleError::KeyNotFound { .. })
+    ));
+    tree.delete(&key2).unwrap();
+    assert_eq!(tree.num_leaves().unwrap(), 0);
+    assert_eq!(tree.root().unwrap(), empty_root);
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_sparse_proofs() {
+    let hasher = PoseidonHasher;
+    let mut tree = SparseMerkleTree::new(PoseidonHasher, MemoryStore::new());
+
+    let keys = (1..=10).map(|i| node(i * 0x1234567)).collect::<Vec<Node>>();
+    for (i, key) in keys.iter().enumerate() {
+        tree.insert(key, node(i as u64 + 1)).unwrap();
+    }
+
+    // Inclusion proofs
+    for (i, key) in keys.iter().enumerate() {
+        let proof = tree.proof(key).unwrap();
+        assert_eq!(proof.value, Some(node(i as u64 + 1)));
+        assert!(tree.verify_proof(&proof));
+    }
+
+    // Non-inclusion proof
+    let missing = node(0x1234568);
+    let mut proof = tree.proof(&missing).unwrap();
+    assert_eq!(proof.value, None);
+    assert!(proof.verify(&hasher));
+
+    // A missing key can't be proven to be in the tree, nor a present one to be missing.
+    proof.value = Some(node(1));
+    assert!(!proof.verify(&hasher));
+    let mut proof = tree.proof(&keys[0]).unwrap();
+    proof.value = None;
+    assert!(!proof.verify(&hasher));
+}
+
+// Inserts two keys and deletes one, then checks the tree after reopening the store.
+#[cfg(any(feature = "sled_store", feature = "sqlite_store"))]
+fn check_persisted<S: Store>(open: impl Fn() -> S) {
+    let key1 = to_node!("0x0000000000000000000000000000000000000000000000000000000000000001");
+    let key2 = to_node!("0x8000000000000000000000000000000000000000000000000000000000000001");
+    let key3 = to_node!("0x0000000000000000000000000000000000000000000000000000000000000003");
+    let value = to_node!("0x0000000000000000000000000000000000000000000000000000000000000011");
+
+    let root = {
+        let mut tree = SparseMerkleTree::new(Keccak256Hasher, open());
+        tree.insert(&key1, value).unwrap();
+        tree.insert(&key2, value).unwrap();
+        tree.insert(&key3, value).unwrap();
+        tree.delete(&key3).unwrap();
+        tree.root().unwrap()
+    };
+
+    let mut tree = SparseMerkleTree::new(Keccak256Hasher, open());
+    assert_eq!(tree.root().unwrap(), root);
+    assert_eq!(tree.num_leaves().unwrap(), 2);
+    assert_eq!(tree.get(&key1).unwrap(), Some(value));
+    assert_eq!(tree.get(&key3).unwrap(), None);
+    assert!(tree.verify_proof(&tree.proof(&key2).unwrap()));
+
+    // Emptying the tree is persisted too.
+    tree.delete(&key1).unwrap();
+    tree.delete(&key2).unwrap();
+    let empty_root = tree.root().unwrap();
+    drop(tree);
+    let tree = SparseMerkleTree::new(Keccak256Hasher, open());
+    assert_eq!(tree.root().unwrap(), empty_root);
+    assert_eq!(tree.num_leaves().unwrap(), 0);
+}
+
+#[cfg(feature = "sqlite_store")]
+#[test]
+fn test_sparse_persisted_sqlite() {
+    let temp_file = temp_file::TempFile::with_suffix("_sqlite.db").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+    check_persisted(|| SqliteStore::new(path));
+}
+
+#[cfg(feature = "sled_store")]
+#[test]
+fn test_sparse_persisted_sled() {
+    let temp_file = temp_file::TempFile::with_suffix("_sled.db").unwrap();
+    let path = temp_file.path().to_str().unwrap().to_owned();
+    // Sled expects a directory, so the file can't exist.
+    temp_file.cleanup().unwrap();
+    check_persisted(|| SledStore::new(&path, false));
+    std::fs::remove_dir_all(&path).unwrap();
+}
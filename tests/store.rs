@@ -1,5 +1,10 @@
 // Copyright 2025 Bilinear Labs - MIT License
 
+// The original tests push the stores enabled by each feature.
+#![allow(clippy::vec_init_then_push)]
+
+#[cfg(any(feature = "sled_store", feature = "sqlite_store"))]
+use rs_merkle_tree::MerkleError;
 use rs_merkle_tree::{node::Node, to_node, Store};
 #[cfg(feature = "rocksdb_store")]
 use std::fs;
@@ -79,6 +84,18 @@ fn test_stores_single() {
                 "0x1230000000000000000000000000000000000000000000000000000000000000"
             ))
         );
+
+        // Overwriting an existing leaf doesn't change the number of leaves.
+        store.put(&[(0, 1, Node::ZERO)]).unwrap();
+        assert_eq!(store.get_num_leaves(), 4);
+
+        // Metadata is stored along with the nodes.
+        assert_eq!(store.get_metadata("SOME_KEY").unwrap(), None);
+        store
+            .put_with_metadata(&[(0, 4, Node::ZERO)], &[("SOME_KEY", &[1, 2, 3])])
+            .unwrap();
+        assert_eq!(store.get_num_leaves(), 5);
+        assert_eq!(store.get_metadata("SOME_KEY").unwrap(), Some(vec![1, 2, 3]));
     }
 
     // Now delete the RocksDB directory.
@@ -247,14 +264,97 @@ fn test_stores_multiple() {
                 .collect::<Vec<Option<Node>>>()
         );
 
+        // Big reads return the nodes in order.
+        let levels = vec![0u32; 1_000];
+        let indices = (0..1_000u64).map(|i| i % 8).collect::<Vec<u64>>();
+        assert_eq!(
+            store.get(&levels, &indices).unwrap(),
+            indices
+                .iter()
+                .map(|i| Some(level_0[*i as usize]))
+                .collect::<Vec<Option<Node>>>()
+        );
+
         // Fetch 4 levels/indexes that dont have any content.
         assert_eq!(
             store.get(&[4, 5, 6, 7], &[111, 13, 22, 99]).unwrap(),
             vec![None, None, None, None]
         );
+
+        // Scan ranges of nodes in order, skipping missing ones.
+        assert_eq!(
+            store.get_range(0, 2, 6).unwrap(),
+            (2..6).map(|i| (i, level_0[i as usize])).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            store.get_range(1, 0, 100).unwrap(),
+            (0..4).map(|i| (i, level_1[i as usize])).collect::<Vec<_>>()
+        );
+        assert_eq!(store.get_range(2, 1, 1).unwrap(), vec![]);
+        assert_eq!(store.get_range(4, 0, 100).unwrap(), vec![]);
+
+        // Truncate to 5 leaves, rewriting one node in the same batch.
+        store
+            .truncate(5, &[(1, 2, Node::ZERO)], &[("SOME_KEY", &[1])])
+            .unwrap();
+        assert_eq!(store.get_num_leaves(), 5);
+        assert_eq!(store.get_metadata("SOME_KEY").unwrap(), Some(vec![1]));
+        assert_eq!(
+            store
+                .get(&[0, 0, 1, 1, 2, 2, 3], &[4, 5, 2, 3, 0, 1, 0])
+                .unwrap(),
+            vec![
+                Some(level_0[4]),
+                None,
+                Some(Node::ZERO),
+                None,
+                Some(level_2[0]),
+                Some(level_2[1]),
+                Some(level_3[0])
+            ]
+        );
+
+        // Truncating to 0 removes all nodes.
+        store.truncate(0, &[], &[]).unwrap();
+        assert_eq!(store.get_num_leaves(), 0);
+        assert_eq!(
+            store.get(&[0, 1, 2, 3], &[0, 0, 0, 0]).unwrap(),
+            vec![None, None, None, None]
+        );
     }
 
     // Now delete the RocksDB directory.
     #[cfg(feature = "rocksdb_store")]
     fs::remove_dir_all(path_rocksdb).expect("Failed to delete RocksDB file");
 }
+
+#[cfg(feature = "sqlite_store")]
+#[test]
+fn test_sqlite_try_new_corrupt_file() {
+    let temp_file = TempFile::with_suffix("_sqlite.db")
+        .unwrap()
+        .with_contents(b"not a sqlite database")
+        .unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    assert!(matches!(
+        SqliteStore::try_new(path),
+        Err(MerkleError::StoreInitError(_))
+    ));
+}
+
+#[cfg(feature = "sled_store")]
+#[test]
+fn test_sled_try_new_locked() {
+    let temp_file = TempFile::with_suffix("_sled.db").unwrap();
+    let path = temp_file.path().to_str().unwrap().to_owned();
+    // Sled expects a directory, so the file can't exist.
+    temp_file.cleanup().unwrap();
+
+    // The database is locked while the first store is open.
+    let _store = SledStore::try_new(&path, true).unwrap();
+    assert!(matches!(
+        SledStore::try_new(&path, true),
+        Err(MerkleError::StoreOpenError(_))
+    ));
+}
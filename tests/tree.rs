@@ -1,8 +1,36 @@
 // Copyright 2025 Bilinear Labs - MIT License
 
+// The original tests predate these lints.
+#![allow(clippy::needless_borrow, clippy::bool_assert_comparison)]
+
+mod common;
+
+#[cfg(any(feature = "memory_store", feature = "sqlite_store"))]
+use common::leaves;
+#[cfg(feature = "memory_store")]
+use common::node;
 use rs_merkle_tree::hasher::Keccak256Hasher;
-use rs_merkle_tree::{to_node, MerkleTree, Node, Store};
+#[cfg(feature = "memory_store")]
+use rs_merkle_tree::hasher::{Hasher, PoseidonHasher};
+#[cfg(any(
+    feature = "memory_store",
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
+use rs_merkle_tree::Store;
+use rs_merkle_tree::{to_node, MerkleError, MerkleTree, Node};
+#[cfg(any(
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
 use std::fs;
+#[cfg(any(
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
 use std::path::Path;
 
 #[cfg(feature = "memory_store")]
@@ -16,6 +44,11 @@ use rs_merkle_tree::stores::SqliteStore;
 #[cfg(feature = "memory_store")]
 use rs_merkle_tree::tree::MerkleTree32;
 
+#[cfg(any(
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
 fn dir_size(path: &Path) -> u64 {
     if path.is_file() {
         return path.metadata().map(|m| m.len()).unwrap_or(0);
@@ -74,6 +107,642 @@ fn test_merkle_tree_keccak_32_memory() {
     // TODO: Once async is implemented, ensure proofs are always consistent.
 }
 
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_update_leaf() {
+    let mut leaves = (0..16).map(node).collect::<Vec<Node>>();
+
+    let mut tree: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    tree.add_leaves(&leaves).unwrap();
+
+    // Update a leaf in place. The number of leaves does not change.
+    let new_leaf = to_node!("0x1230000000000000000000000000000000000000000000000000000000000000");
+    tree.update_leaf(5, new_leaf).unwrap();
+    assert_eq!(tree.num_leaves(), 16);
+
+    // The root matches a tree built with the updated leaf from scratch.
+    leaves[5] = new_leaf;
+    let mut expected: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    expected.add_leaves(&leaves).unwrap();
+    assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+
+    // Proofs are consistent with the new root.
+    let proof = tree.proof(5).unwrap();
+    assert_eq!(proof.leaf, new_leaf);
+    assert!(tree.verify_proof(&proof).unwrap());
+
+    // Leaves can still be appended after an update.
+    tree.add_leaves(&[new_leaf]).unwrap();
+    expected.add_leaves(&[new_leaf]).unwrap();
+    assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+
+    // Leaves that were never appended can't be updated.
+    assert!(matches!(
+        tree.update_leaf(17, new_leaf),
+        Err(MerkleError::LeafIndexOutOfBounds {
+            index: 17,
+            num_leaves: 17
+        })
+    ));
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_try_new_capacity_exceeded() {
+    // A store written by a deeper tree can't be opened with a smaller depth.
+    let mut store = MemoryStore::default();
+    store.put(&[(0, 8, Node::ZERO)]).unwrap();
+
+    assert!(matches!(
+        MerkleTree::<Keccak256Hasher, MemoryStore, 3>::try_new(Keccak256Hasher, store),
+        Err(MerkleError::CapacityExceeded {
+            num_leaves: 9,
+            capacity: 8
+        })
+    ));
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_update_leaves() {
+    let mut leaves = (0..1_000).map(node).collect::<Vec<Node>>();
+
+    let mut tree: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    tree.add_leaves(&leaves).unwrap();
+
+    // Update every third leaf plus a repeated index where the last value wins.
+    let mut updates = (0..1_000u64)
+        .step_by(3)
+        .map(|i| (i, node(i + 5_000)))
+        .collect::<Vec<(u64, Node)>>();
+    updates.push((999, Node::ZERO));
+    updates.push((999, node(7_000)));
+    tree.update_leaves(&updates).unwrap();
+    assert_eq!(tree.num_leaves(), 1_000);
+
+    for (index, leaf) in &updates {
+        leaves[*index as usize] = *leaf;
+    }
+    let mut expected: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    expected.add_leaves(&leaves).unwrap();
+    assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+
+    // Nothing is written if any of the indices is out of bounds.
+    assert!(matches!(
+        tree.update_leaves(&[(0, Node::ZERO), (1_000, Node::ZERO)]),
+        Err(MerkleError::LeafIndexOutOfBounds {
+            index: 1_000,
+            num_leaves: 1_000
+        })
+    ));
+    assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_remove_leaf() {
+    let leaves = leaves(8);
+
+    let mut tree: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    tree.add_leaves(&leaves).unwrap();
+    assert_eq!(tree.num_live_leaves().unwrap(), 8);
+
+    // Removing a leaf is the same as appending an empty one in its slot.
+    tree.remove_leaf(2).unwrap();
+    let mut expected: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    expected.add_leaves(&leaves[..2]).unwrap();
+    expected.add_leaves(&[Node::ZERO]).unwrap();
+    expected.add_leaves(&leaves[3..]).unwrap();
+    assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+    assert_eq!(tree.num_leaves(), 8);
+    assert_eq!(tree.num_live_leaves().unwrap(), 7);
+
+    // Removing it twice has no effect.
+    tree.remove_leaf(2).unwrap();
+    assert_eq!(tree.num_live_leaves().unwrap(), 7);
+
+    // Once the right half is removed the root matches a tree where it was never filled.
+    for index in 4..8 {
+        tree.remove_leaf(index).unwrap();
+    }
+    tree.update_leaf(2, leaves[2]).unwrap();
+    let mut expected: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    expected.add_leaves(&leaves[..4]).unwrap();
+    assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+    assert_eq!(
+        tree.proof(1).unwrap().proof,
+        expected.proof(1).unwrap().proof
+    );
+    assert_eq!(tree.num_leaves(), 8);
+    assert_eq!(tree.num_live_leaves().unwrap(), 4);
+
+    // Leaves that were never appended can't be removed.
+    assert!(tree.remove_leaf(8).is_err());
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_truncate() {
+    let leaves = leaves(20);
+
+    let mut tree: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    tree.add_leaves(&leaves[..10]).unwrap();
+    tree.add_leaves(&[Node::ZERO, Node::ZERO]).unwrap();
+    assert_eq!(tree.num_live_leaves().unwrap(), 10);
+
+    // Roll back to 6 leaves. It's the same as if only 6 leaves were appended.
+    tree.truncate(6).unwrap();
+    let mut expected: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    expected.add_leaves(&leaves[..6]).unwrap();
+    assert_eq!(tree.num_leaves(), 6);
+    assert_eq!(tree.num_live_leaves().unwrap(), 6);
+    assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+    assert_eq!(tree.proof(7).unwrap().leaf, Node::ZERO);
+
+    // Leaves are appended again from the new length.
+    tree.add_leaves(&leaves[10..]).unwrap();
+    expected.add_leaves(&leaves[10..]).unwrap();
+    assert_eq!(tree.num_leaves(), 16);
+    assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+
+    // Truncating to the current length does nothing, and the tree can't grow.
+    tree.truncate(16).unwrap();
+    assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+    assert!(tree.truncate(17).is_err());
+
+    // Truncating everything leaves an empty tree.
+    tree.truncate(0).unwrap();
+    let empty: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    assert_eq!(tree.num_leaves(), 0);
+    assert_eq!(tree.root().unwrap(), empty.root().unwrap());
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_root_at() {
+    let leaves = leaves(100);
+
+    // Record the root after each append.
+    let mut tree: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    let mut roots = vec![tree.root().unwrap()];
+    for leaf in &leaves {
+        tree.add_leaves(&[*leaf]).unwrap();
+        roots.push(tree.root().unwrap());
+    }
+
+    // Every historical root can be computed from the final tree.
+    for (num_leaves, root) in roots.iter().enumerate() {
+        assert_eq!(tree.root_at(num_leaves as u64).unwrap(), *root);
+    }
+
+    // Which tree size produced a given root.
+    let size = (0..=tree.num_leaves()).find(|size| tree.root_at(*size).unwrap() == roots[42]);
+    assert_eq!(size, Some(42));
+
+    // Sizes beyond the current one are unknown.
+    assert!(matches!(
+        tree.root_at(101),
+        Err(MerkleError::LeafIndexOutOfBounds {
+            index: 101,
+            num_leaves: 100
+        })
+    ));
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_proof_at() {
+    let leaves = leaves(40);
+
+    // Record all the proofs after each append.
+    let mut tree: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    let mut proofs = Vec::new();
+    for leaf in &leaves {
+        tree.add_leaves(&[*leaf]).unwrap();
+        proofs.push(
+            (0..tree.num_leaves())
+                .map(|i| tree.proof(i).unwrap())
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    // Historical proofs match the ones obtained back then.
+    for (size, size_proofs) in proofs.iter().enumerate() {
+        let tree_size = size as u64 + 1;
+        for (leaf_idx, expected) in size_proofs.iter().enumerate() {
+            let proof = tree.proof_at(leaf_idx as u64, tree_size).unwrap();
+            assert_eq!(proof.proof, expected.proof);
+            assert_eq!(proof.leaf, expected.leaf);
+            assert_eq!(proof.index, expected.index);
+            assert_eq!(proof.root, expected.root);
+            assert_eq!(proof.root, tree.root_at(tree_size).unwrap());
+            assert!(tree.verify_proof(&proof).unwrap());
+        }
+    }
+
+    // The leaf must be within the historical tree, which can't exceed the current one.
+    assert!(tree.proof_at(10, 10).is_err());
+    assert!(tree.proof_at(10, 41).is_err());
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_root_history() {
+    let mut tree: MerkleTree32 =
+        MerkleTree::new(Keccak256Hasher, MemoryStore::default()).with_root_history(3);
+    assert!(tree.recent_roots().unwrap().is_empty());
+    assert!(tree.is_known_root(&tree.root().unwrap()).unwrap());
+
+    // One root is recorded for each call, keeping only the last 3.
+    let mut roots = Vec::new();
+    for batch in 0..5 {
+        let leaves = (0..4)
+            .map(|i| node(batch * 4 + i + 1))
+            .collect::<Vec<Node>>();
+        tree.add_leaves(&leaves).unwrap();
+        roots.push(tree.root().unwrap());
+    }
+    assert_eq!(tree.recent_roots().unwrap(), roots[2..]);
+    assert!(!tree.is_known_root(&roots[1]).unwrap());
+    assert!(tree.is_known_root(&roots[2]).unwrap());
+    assert!(tree.is_known_root(&roots[4]).unwrap());
+
+    // Roots after a rollback are forgotten.
+    tree.truncate(12).unwrap();
+    assert_eq!(tree.recent_roots().unwrap(), roots[2..3]);
+    assert!(!tree.is_known_root(&roots[4]).unwrap());
+
+    // Truncating with the history disabled keeps the persisted roots that remain valid.
+    tree.add_leaves(&[node(100)]).unwrap();
+    let mut tree = tree.with_root_history(0);
+    tree.truncate(12).unwrap();
+    let tree = tree.with_root_history(3);
+    assert_eq!(tree.recent_roots().unwrap(), roots[2..3]);
+}
+
+#[cfg(feature = "memory_store")]
+fn check_multi_proof<H: Hasher>(new_hasher: fn() -> H) {
+    let leaves = leaves(500);
+
+    let mut tree: MerkleTree<H, MemoryStore, 32> =
+        MerkleTree::new(new_hasher(), MemoryStore::default());
+    tree.add_leaves(&leaves).unwrap();
+
+    // Prove a set of leaves, some of them repeated and some empty.
+    let indices = [7, 3, 0, 1, 250, 251, 499, 3, 600, 1_000_000];
+    let proof = tree.multi_proof(&indices).unwrap();
+    assert_eq!(
+        proof.indices,
+        vec![0, 1, 3, 7, 250, 251, 499, 600, 1_000_000]
+    );
+    assert_eq!(
+        proof.leaves[..7],
+        [0, 1, 3, 7, 250, 251, 499].map(|i| leaves[i])
+    );
+    assert_eq!(proof.leaves[7..], [Node::ZERO, Node::ZERO]);
+    assert_eq!(proof.root, tree.root().unwrap());
+    assert!(proof.verify(&new_hasher()));
+
+    // It's smaller than a proof per leaf.
+    assert!(proof.proof.len() < 9 * 32);
+
+    // Proving all the leaves only needs the empty siblings on the right.
+    let all = tree.multi_proof(&(0..512).collect::<Vec<u64>>()).unwrap();
+    assert_eq!(all.proof.len(), 32 - 9);
+    assert!(all.verify(&new_hasher()));
+
+    // Tampered proofs don't verify.
+    let mut bad = tree.multi_proof(&indices).unwrap();
+    bad.leaves[2] = leaves[2];
+    assert!(!bad.verify(&new_hasher()));
+    let mut bad = tree.multi_proof(&indices).unwrap();
+    bad.proof.pop();
+    assert!(!bad.verify(&new_hasher()));
+    let mut bad = tree.multi_proof(&indices).unwrap();
+    bad.indices.swap(0, 1);
+    assert!(!bad.verify(&new_hasher()));
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_multi_proof_keccak() {
+    check_multi_proof(|| Keccak256Hasher);
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_multi_proof_poseidon() {
+    check_multi_proof(|| PoseidonHasher);
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_range_proof() {
+    let leaves = leaves(100);
+
+    let mut tree: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    tree.add_leaves(&leaves).unwrap();
+
+    // Ranges of different sizes and alignments.
+    for (start, end) in [(0, 100), (3, 4), (8, 16), (37, 81), (0, 1), (99, 100)] {
+        let proof = tree.range_proof(start, end).unwrap();
+        assert_eq!(proof.root, tree.root().unwrap());
+        assert!(proof.left.len() + proof.right.len() <= 2 * 32);
+        assert!(proof.verify(&Keccak256Hasher, &leaves[start as usize..end as usize]));
+    }
+
+    // Ranges beyond the number of leaves are proven against empty leaves.
+    let proof = tree.range_proof(90, 120).unwrap();
+    let mut expected = leaves[90..].to_vec();
+    expected.resize(30, Node::ZERO);
+    assert!(proof.verify(&Keccak256Hasher, &expected));
+
+    // Wrong or missing leaves don't verify.
+    let proof = tree.range_proof(37, 81).unwrap();
+    assert!(!proof.verify(&Keccak256Hasher, &leaves[38..82]));
+    assert!(!proof.verify(&Keccak256Hasher, &leaves[37..80]));
+
+    // Empty ranges can't be proven.
+    assert!(matches!(
+        tree.range_proof(10, 10),
+        Err(MerkleError::EmptyRange { start: 10, end: 10 })
+    ));
+    assert!(matches!(
+        tree.range_proof(12, 10),
+        Err(MerkleError::EmptyRange { start: 12, end: 10 })
+    ));
+    assert!(matches!(
+        tree.range_proof(10, (1 << 32) + 1),
+        Err(MerkleError::LeafIndexOutOfBounds { .. })
+    ));
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_consistency_proof() {
+    let leaves = leaves(50);
+
+    let mut tree: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    tree.add_leaves(&leaves).unwrap();
+
+    // Every pair of sizes is consistent.
+    for old_size in 0..=50 {
+        for new_size in old_size..=50 {
+            let proof = tree.consistency_proof(old_size, new_size).unwrap();
+            let old_root = tree.root_at(old_size).unwrap();
+            let new_root = tree.root_at(new_size).unwrap();
+            assert!(proof.verify(&Keccak256Hasher, &old_root, &new_root));
+        }
+    }
+
+    // Roots swapped or from other sizes don't verify.
+    let proof = tree.consistency_proof(13, 37).unwrap();
+    let old_root = tree.root_at(13).unwrap();
+    let new_root = tree.root_at(37).unwrap();
+    assert!(!proof.verify(&Keccak256Hasher, &new_root, &old_root));
+    assert!(!proof.verify(&Keccak256Hasher, &tree.root_at(12).unwrap(), &new_root));
+    assert!(!proof.verify(&Keccak256Hasher, &old_root, &tree.root_at(38).unwrap()));
+
+    // A tree that rewrote history is not consistent with the old root.
+    tree.update_leaf(5, Node::ZERO).unwrap();
+    let proof = tree.consistency_proof(13, 50).unwrap();
+    assert!(!proof.verify(&Keccak256Hasher, &old_root, &tree.root().unwrap()));
+
+    // The old size can't be greater than the new one.
+    assert!(tree.consistency_proof(14, 13).is_err());
+    assert!(tree.consistency_proof(13, 51).is_err());
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_index_of() {
+    let leaves = leaves(20);
+
+    let mut tree: MerkleTree32 =
+        MerkleTree::new(Keccak256Hasher, MemoryStore::default()).with_leaf_index();
+    tree.add_leaves(&leaves[..10]).unwrap();
+    tree.add_leaves(&[Node::ZERO]).unwrap();
+    tree.add_leaves(&leaves[10..]).unwrap();
+
+    for (i, leaf) in leaves.iter().enumerate() {
+        let index = if i < 10 { i as u64 } else { i as u64 + 1 };
+        assert_eq!(tree.index_of(leaf).unwrap(), Some(index));
+
+        let proof = tree.proof_for_leaf(leaf).unwrap().unwrap();
+        assert_eq!(proof.index, index);
+        assert!(tree.verify_proof(&proof).unwrap());
+    }
+    assert_eq!(tree.index_of(&Node::ZERO).unwrap(), None);
+
+    // Updated leaves are found at their index and old values are forgotten.
+    let new_leaf = to_node!("0x1230000000000000000000000000000000000000000000000000000000000000");
+    tree.update_leaf(3, new_leaf).unwrap();
+    assert_eq!(tree.index_of(&new_leaf).unwrap(), Some(3));
+    assert_eq!(tree.index_of(&leaves[3]).unwrap(), None);
+
+    // Same for removed and truncated leaves.
+    tree.remove_leaf(4).unwrap();
+    assert_eq!(tree.index_of(&leaves[4]).unwrap(), None);
+    tree.truncate(15).unwrap();
+    assert_eq!(tree.index_of(&leaves[13]).unwrap(), Some(14));
+    assert_eq!(tree.index_of(&leaves[14]).unwrap(), None);
+    assert!(tree.proof_for_leaf(&leaves[14]).unwrap().is_none());
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_index_of_duplicates() {
+    let leaf = node(1);
+    let mut tree: MerkleTree32 =
+        MerkleTree::new(Keccak256Hasher, MemoryStore::default()).with_leaf_index();
+
+    // The same leaf twice in a batch, then once more.
+    tree.add_leaves(&[leaf, node(2), leaf]).unwrap();
+    tree.add_leaves(&[node(3), leaf]).unwrap();
+    assert_eq!(tree.index_of(&leaf).unwrap(), Some(4));
+
+    // Once the latest copy is gone, the previous ones are found.
+    tree.truncate(4).unwrap();
+    assert_eq!(tree.index_of(&leaf).unwrap(), Some(2));
+    tree.remove_leaf(2).unwrap();
+    assert_eq!(tree.index_of(&leaf).unwrap(), Some(0));
+    tree.update_leaf(0, node(4)).unwrap();
+    assert_eq!(tree.index_of(&leaf).unwrap(), None);
+
+    // Writing the leaf back at indices it was at before keeps the others reachable.
+    tree.update_leaf(0, leaf).unwrap();
+    tree.update_leaf(2, leaf).unwrap();
+    tree.remove_leaf(2).unwrap();
+    assert_eq!(tree.index_of(&leaf).unwrap(), Some(0));
+    tree.remove_leaf(0).unwrap();
+    assert_eq!(tree.index_of(&leaf).unwrap(), None);
+}
+
+#[cfg(feature = "sqlite_store")]
+#[test]
+fn test_index_of_persisted() {
+    let temp_file = temp_file::TempFile::with_suffix("_sqlite.db").unwrap();
+    let path = temp_file.path().to_str().unwrap();
+
+    let leaves = leaves(20);
+
+    {
+        let mut tree: MerkleTree<Keccak256Hasher, SqliteStore, 32> =
+            MerkleTree::new(Keccak256Hasher, SqliteStore::new(path)).with_leaf_index();
+        tree.add_leaves(&leaves).unwrap();
+    }
+
+    // The index survives reopening the store.
+    let tree: MerkleTree<Keccak256Hasher, SqliteStore, 32> =
+        MerkleTree::new(Keccak256Hasher, SqliteStore::new(path)).with_leaf_index();
+    assert_eq!(tree.index_of(&leaves[7]).unwrap(), Some(7));
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_leaves() {
+    let mut leaves = leaves(3_000);
+
+    let mut tree: MerkleTree32 = MerkleTree::new(Keccak256Hasher, MemoryStore::default());
+    tree.add_leaves(&leaves).unwrap();
+    tree.remove_leaf(1_500).unwrap();
+    leaves[1_500] = Node::ZERO;
+
+    // Iterate over all the leaves, which spans multiple pages.
+    let all = tree.leaves(..).collect::<Result<Vec<Node>, _>>().unwrap();
+    assert_eq!(all, leaves);
+
+    // Paginate them.
+    let page = tree
+        .leaves(1_000..1_100)
+        .collect::<Result<Vec<Node>, _>>()
+        .unwrap();
+    assert_eq!(page, leaves[1_000..1_100]);
+    let page = tree
+        .leaves(2_990..=3_010)
+        .collect::<Result<Vec<Node>, _>>()
+        .unwrap();
+    assert_eq!(page, leaves[2_990..]);
+    assert_eq!(tree.leaves(5_000..).count(), 0);
+
+    assert_eq!(tree.get_leaf(42).unwrap(), leaves[42]);
+    assert_eq!(tree.get_leaf(1_500).unwrap(), Node::ZERO);
+    assert!(tree.get_leaf(3_000).is_err());
+}
+
+#[cfg(all(feature = "parallel", feature = "memory_store"))]
+#[test]
+fn test_parallel_add_leaves_matches_sequential() {
+    // Batches of at least 1024 nodes per level are hashed in parallel.
+    const PARALLEL_THRESHOLD: usize = 1024;
+
+    fn check<H: Hasher>(new_hasher: fn() -> H) {
+        let leaves = leaves(3 * PARALLEL_THRESHOLD as u64 + 7);
+
+        // A single batch is hashed in parallel on the lower levels.
+        let mut tree = MerkleTree::<H, MemoryStore, 20>::new(new_hasher(), MemoryStore::new());
+        tree.add_leaves(&leaves).unwrap();
+
+        // Batches below the threshold are hashed sequentially.
+        let mut expected = MerkleTree::<H, MemoryStore, 20>::new(new_hasher(), MemoryStore::new());
+        for chunk in leaves.chunks(PARALLEL_THRESHOLD - 1) {
+            expected.add_leaves(chunk).unwrap();
+        }
+
+        assert_eq!(tree.root().unwrap(), expected.root().unwrap());
+        for i in [0, 1_000, leaves.len() as u64 - 1] {
+            assert_eq!(
+                tree.proof(i).unwrap().proof,
+                expected.proof(i).unwrap().proof
+            );
+        }
+    }
+
+    check(|| Keccak256Hasher);
+    check(|| PoseidonHasher);
+}
+
+#[cfg(any(
+    feature = "memory_store",
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
+fn check_depth_64<S: Store>(mut store: S) {
+    // Appending 2^63 leaves is not feasible, so start with a store that already has an
+    // empty leaf right before the last two slots.
+    store.put(&[(0, u64::MAX - 3, Node::ZERO)]).unwrap();
+
+    let mut tree: MerkleTree<Keccak256Hasher, S, 64> = MerkleTree::new(Keccak256Hasher, store);
+    assert_eq!(tree.num_leaves(), u64::MAX - 2);
+
+    let leaves = [
+        to_node!("0x0000000000000000000000000000000000000000000000000000000000000001"),
+        to_node!("0x0000000000000000000000000000000000000000000000000000000000000002"),
+    ];
+    tree.add_leaves(&leaves[..1]).unwrap();
+    let root = tree.root().unwrap();
+    tree.add_leaves(&leaves[1..]).unwrap();
+    assert_eq!(tree.num_leaves(), u64::MAX);
+
+    for (index, leaf) in [(u64::MAX - 2, leaves[0]), (u64::MAX - 1, leaves[1])] {
+        let proof = tree.proof(index).unwrap();
+        assert_eq!(proof.leaf, leaf);
+        assert!(proof.verify(&Keccak256Hasher));
+    }
+    assert_eq!(
+        tree.leaves(u64::MAX - 3..)
+            .collect::<Result<Vec<Node>, MerkleError>>()
+            .unwrap(),
+        vec![Node::ZERO, leaves[0], leaves[1]]
+    );
+
+    // The number of leaves is a u64, so the last slot can't be appended.
+    assert!(matches!(
+        tree.add_leaves(&[Node::ZERO]),
+        Err(MerkleError::TreeFull {
+            depth: 64,
+            capacity: u64::MAX
+        })
+    ));
+
+    tree.truncate(u64::MAX - 1).unwrap();
+    assert_eq!(tree.root().unwrap(), root);
+}
+
+#[cfg(feature = "memory_store")]
+#[test]
+fn test_depth_64_memory() {
+    check_depth_64(MemoryStore::default());
+}
+
+#[cfg(feature = "sled_store")]
+#[test]
+fn test_depth_64_sled() {
+    let temp_file = temp_file::TempFile::with_suffix("_sled.db").unwrap();
+    let path = temp_file.path().to_str().unwrap().to_owned();
+    // Sled expects a directory, so the file can't exist.
+    temp_file.cleanup().unwrap();
+    check_depth_64(SledStore::new(&path, true));
+}
+
+#[cfg(feature = "sqlite_store")]
+#[test]
+fn test_depth_64_sqlite() {
+    let temp_file = temp_file::TempFile::with_suffix("_sqlite.db").unwrap();
+    check_depth_64(SqliteStore::new(temp_file.path().to_str().unwrap()));
+}
+
+#[cfg(feature = "rocksdb_store")]
+#[test]
+fn test_depth_64_rocksdb() {
+    let temp_file = temp_file::TempFile::with_suffix("_rocksdb.db").unwrap();
+    let path = temp_file.path().to_str().unwrap().to_owned();
+    // RocksDB expects the file to not exist.
+    temp_file.cleanup().unwrap();
+    check_depth_64(RocksDbStore::new(&path));
+}
+
 #[cfg(any(
     feature = "sled_store",
     feature = "sqlite_store",
@@ -123,6 +792,11 @@ fn test_disk_space() {
     bench_store::<RocksDbStore, _>("rocksdb.db", || RocksDbStore::new("rocksdb.db"));
 }
 
+#[cfg(any(
+    feature = "sled_store",
+    feature = "sqlite_store",
+    feature = "rocksdb_store"
+))]
 fn print_size(name: &str, file: &str, num_leaves: u64) {
     // Hardcoded depth for now.
     let depth = 32;
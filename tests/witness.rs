@@ -0,0 +1,49 @@
+// Copyright 2025 Bilinear Labs - MIT License
+
+#![cfg(feature = "memory_store")]
+
+mod common;
+
+use common::leaves;
+use rs_merkle_tree::hasher::Keccak256Hasher;
+use rs_merkle_tree::stores::MemoryStore;
+use rs_merkle_tree::{MerkleTree, WitnessTree};
+
+#[test]
+fn test_witness_proofs() {
+    let leaves = leaves(100);
+
+    let mut witness = WitnessTree::<Keccak256Hasher, 8>::new(Keccak256Hasher);
+    let mut tree =
+        MerkleTree::<Keccak256Hasher, MemoryStore, 8>::new(Keccak256Hasher, MemoryStore::new());
+
+    let marked = [0, 1, 6, 31, 32, 57, 99];
+    for (i, leaf) in leaves.iter().enumerate() {
+        witness.add_leaves(&[*leaf]).unwrap();
+        tree.add_leaves(&[*leaf]).unwrap();
+        if marked.contains(&i) {
+            assert_eq!(witness.mark(), Some(i as u64));
+        }
+
+        // Proofs of marked leaves match the full tree after each leaf.
+        assert_eq!(witness.root(), tree.root().unwrap());
+        for index in witness.marked() {
+            let proof = witness.proof(index).unwrap();
+            let expected = tree.proof(index).unwrap();
+            assert_eq!(proof.proof, expected.proof);
+            assert_eq!(proof.leaf, expected.leaf);
+            assert_eq!(proof.root, expected.root);
+            assert!(tree.verify_proof(&proof).unwrap());
+        }
+    }
+
+    // Unmarked leaves have no proof.
+    assert!(witness.proof(2).is_none());
+    assert!(witness.unmark(6));
+    assert!(!witness.unmark(6));
+    assert!(witness.proof(6).is_none());
+    assert_eq!(
+        witness.marked().collect::<Vec<u64>>(),
+        [0, 1, 31, 32, 57, 99]
+    );
+}